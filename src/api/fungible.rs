@@ -19,7 +19,7 @@ use lnpbp::bitcoin::OutPoint;
 use lnpbp::bp::blind::OutpointReveal;
 use lnpbp::rgb::{Consignment, ContractId};
 
-use crate::fungible::{Outcoincealed, Outcoins};
+use crate::fungible::{DecimalAmount, Outcoincealed, Outcoins};
 use crate::util::SealSpec;
 use crate::DataFormat;
 
@@ -59,7 +59,9 @@ pub enum Request {
     Allocations(ContractId),
 }
 
-#[derive(Clap, Clone, PartialEq, StrictEncode, StrictDecode, Debug, Display)]
+#[derive(
+    Clap, Clone, PartialEq, StrictEncode, StrictDecode, Debug, Display,
+)]
 #[display(Debug)]
 #[cfg_attr(
     feature = "serde",
@@ -80,7 +82,7 @@ pub struct Issue {
 
     /// Limit for the total supply; ignored if the asset can't be inflated
     #[clap(short, long)]
-    pub supply: Option<f32>,
+    pub supply: Option<DecimalAmount>,
 
     /// Enables secondary issuance/inflation; takes UTXO seal definition
     /// as its value
@@ -43,6 +43,67 @@ pub trait Cache {
     ) -> Result<BTreeMap<ContractId, Vec<AtomicValue>>, CacheError>;
 }
 
+/// Persistent storage for the assets created with `Processor`. Unlike [`Cache`]
+/// it returns owned asset data, so it can be shared between threads behind a
+/// mutex. Each [`Cache`] serves as asset storage.
+pub trait AssetStorage: Send {
+    fn assets(&self) -> Result<Vec<Asset>, CacheError>;
+    fn asset(&self, id: ContractId) -> Result<Asset, CacheError>;
+
+    /// Returns known assets issued under the schema with `schema_id`
+    fn assets_by_schema(
+        &self,
+        schema_id: SchemaId,
+    ) -> Result<Vec<Asset>, CacheError> {
+        Ok(self
+            .assets()?
+            .into_iter()
+            .filter(|asset| *asset.schema_id() == schema_id)
+            .collect())
+    }
+
+    fn has_asset(&self, id: ContractId) -> Result<bool, CacheError>;
+    fn add_asset(&mut self, asset: Asset) -> Result<bool, CacheError>;
+    fn remove_asset(&mut self, id: ContractId) -> Result<bool, CacheError>;
+}
+
+impl<T> AssetStorage for T
+where
+    T: Cache<Error = CacheError> + Send,
+{
+    #[inline]
+    fn assets(&self) -> Result<Vec<Asset>, CacheError> {
+        Ok(Cache::assets(self)?.into_iter().cloned().collect())
+    }
+
+    #[inline]
+    fn asset(&self, id: ContractId) -> Result<Asset, CacheError> {
+        Cache::asset(self, id).map(Asset::clone)
+    }
+
+    #[inline]
+    fn has_asset(&self, id: ContractId) -> Result<bool, CacheError> {
+        Cache::has_asset(self, id)
+    }
+
+    #[inline]
+    fn add_asset(&mut self, asset: Asset) -> Result<bool, CacheError> {
+        Cache::add_asset(self, asset)
+    }
+
+    #[inline]
+    fn remove_asset(&mut self, id: ContractId) -> Result<bool, CacheError> {
+        Cache::remove_asset(self, id)
+    }
+}
+
+/// Storage for the RGB data used by `Processor`, like the fungible assets
+/// schema
+pub trait RgbStorage: Send {
+    fn has_schema(&self, id: SchemaId) -> Result<bool, CacheError>;
+    fn add_schema(&mut self, schema: &Schema) -> Result<bool, CacheError>;
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
 #[display(Debug)]
 pub enum CacheError {
@@ -97,6 +158,12 @@ impl From<FileCacheError> for CacheError {
             FileCacheError::NotFound => {
                 Self::DataIntegrityError("Data file is not found".to_string())
             }
+            FileCacheError::UnsupportedVersion(version) => {
+                Self::DataIntegrityError(format!(
+                    "Unsupported assets file format version {}",
+                    version
+                ))
+            }
         }
     }
 }
@@ -52,8 +52,18 @@ pub enum FileCacheError {
     SerdeToml,
 
     NotFound,
+
+    UnsupportedVersion(u16),
 }
 
+/// Prefix of the strict-encoded assets file, followed by the format version.
+/// Files without it are written in the legacy layout, which starts directly
+/// with the number of the assets.
+const STRICT_MAGIC: [u8; 4] = *b"RGBA";
+
+/// Current version of the strict-encoded assets file format
+const STRICT_FORMAT_VERSION: u16 = 1;
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Display)]
 #[display(Debug)]
 pub struct FileCacheConfig {
@@ -131,11 +141,40 @@ impl FileCache {
                 f.read_to_string(&mut data)?;
                 toml::from_str(&data)?
             }
-            DataFormat::StrictEncode => StrictDecode::strict_decode(f)?,
+            DataFormat::StrictEncode => {
+                let mut data = Vec::new();
+                f.read_to_end(&mut data)?;
+                Self::strict_decode_assets(&data)?
+            }
         };
         Ok(())
     }
 
+    /// Decodes strict-encoded assets file, migrating the assets written in
+    /// the legacy layout (see [`Asset::strict_decode_legacy`])
+    fn strict_decode_assets(
+        data: &[u8],
+    ) -> Result<BTreeMap<ContractId, Asset>, FileCacheError> {
+        if data.starts_with(&STRICT_MAGIC) {
+            let mut reader = &data[STRICT_MAGIC.len()..];
+            let version = u16::strict_decode(&mut reader)?;
+            if version != STRICT_FORMAT_VERSION {
+                Err(FileCacheError::UnsupportedVersion(version))?
+            }
+            return Ok(StrictDecode::strict_decode(&mut reader)?);
+        }
+
+        debug!("Migrating assets file from the legacy layout ...");
+        let mut reader = data;
+        let count = usize::strict_decode(&mut reader)?;
+        let mut assets = bmap![];
+        for _ in 0..count {
+            let id = ContractId::strict_decode(&mut reader)?;
+            assets.insert(id, Asset::strict_decode_legacy(&mut reader)?);
+        }
+        Ok(assets)
+    }
+
     pub fn save(&self) -> Result<(), FileCacheError> {
         trace!("Saving assets information ...");
         let filename = self.config.assets_filename();
@@ -146,7 +185,9 @@ impl FileCache {
             DataFormat::Json => serde_json::to_writer(&f, &self.assets)?,
             DataFormat::Toml => f.write_all(&toml::to_vec(&self.assets)?)?,
             DataFormat::StrictEncode => {
-                self.assets.strict_encode(f)?;
+                f.write_all(&STRICT_MAGIC)?;
+                STRICT_FORMAT_VERSION.strict_encode(&mut f)?;
+                self.assets.strict_encode(&mut f)?;
             }
         }
         Ok(())
@@ -187,7 +228,7 @@ impl Cache for FileCache {
     }
 
     fn add_asset(&mut self, asset: Asset) -> Result<bool, CacheError> {
-        let exists = self.assets.insert(*asset.id(), asset).is_some();
+        let exists = self.assets.insert(asset.contract_id(), asset).is_some();
         self.save()?;
         Ok(exists)
     }
@@ -365,4 +406,25 @@ mod test {
         // Assert caclulation meets expectation
         assert_eq!(expected_map, allocation_map_calculated);
     }
+
+    #[test]
+    fn test_strict_decode_assets() {
+        let empty = BTreeMap::<ContractId, Asset>::new();
+        let mut data = STRICT_MAGIC.to_vec();
+        STRICT_FORMAT_VERSION.strict_encode(&mut data).unwrap();
+        empty.strict_encode(&mut data).unwrap();
+        assert_eq!(FileCache::strict_decode_assets(&data).unwrap(), empty);
+
+        // Legacy files start directly with the number of assets
+        let legacy = strict_encode(&empty).unwrap();
+        assert_eq!(FileCache::strict_decode_assets(&legacy).unwrap(), empty);
+
+        let mut data = STRICT_MAGIC.to_vec();
+        2u16.strict_encode(&mut data).unwrap();
+        empty.strict_encode(&mut data).unwrap();
+        assert!(matches!(
+            FileCache::strict_decode_assets(&data),
+            Err(FileCacheError::UnsupportedVersion(2))
+        ));
+    }
 }
@@ -0,0 +1,122 @@
+// RGB standard library
+// Written in 2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, HashMap};
+
+use lnpbp::rgb::prelude::*;
+
+use super::{AssetStorage, CacheError, RgbStorage};
+use crate::fungible::Asset;
+
+/// Keeps assets in memory only; suitable for tests and light clients which
+/// do not need the assets to persist across restarts
+#[derive(Clone, PartialEq, Debug, Default, Display)]
+#[display(Debug)]
+pub struct InMemoryAssetStorage {
+    assets: HashMap<ContractId, Asset>,
+}
+
+impl InMemoryAssetStorage {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AssetStorage for InMemoryAssetStorage {
+    #[inline]
+    fn assets(&self) -> Result<Vec<Asset>, CacheError> {
+        Ok(self.assets.values().cloned().collect())
+    }
+
+    #[inline]
+    fn asset(&self, id: ContractId) -> Result<Asset, CacheError> {
+        self.assets
+            .get(&id)
+            .cloned()
+            .ok_or(CacheError::NotFound { id: id.to_string() })
+    }
+
+    #[inline]
+    fn has_asset(&self, id: ContractId) -> Result<bool, CacheError> {
+        Ok(self.assets.contains_key(&id))
+    }
+
+    #[inline]
+    fn add_asset(&mut self, asset: Asset) -> Result<bool, CacheError> {
+        Ok(self.assets.insert(asset.contract_id(), asset).is_some())
+    }
+
+    #[inline]
+    fn remove_asset(&mut self, id: ContractId) -> Result<bool, CacheError> {
+        Ok(self.assets.remove(&id).is_some())
+    }
+}
+
+/// Keeps assets and schemata in memory only, serving both as asset and RGB
+/// storage of the `Processor`
+#[derive(Clone, PartialEq, Debug, Default, Display)]
+#[display(Debug)]
+pub struct MemoryCache {
+    assets: InMemoryAssetStorage,
+    schemata: BTreeMap<SchemaId, Schema>,
+}
+
+impl MemoryCache {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AssetStorage for MemoryCache {
+    #[inline]
+    fn assets(&self) -> Result<Vec<Asset>, CacheError> {
+        self.assets.assets()
+    }
+
+    #[inline]
+    fn asset(&self, id: ContractId) -> Result<Asset, CacheError> {
+        self.assets.asset(id)
+    }
+
+    #[inline]
+    fn has_asset(&self, id: ContractId) -> Result<bool, CacheError> {
+        self.assets.has_asset(id)
+    }
+
+    #[inline]
+    fn add_asset(&mut self, asset: Asset) -> Result<bool, CacheError> {
+        self.assets.add_asset(asset)
+    }
+
+    #[inline]
+    fn remove_asset(&mut self, id: ContractId) -> Result<bool, CacheError> {
+        self.assets.remove_asset(id)
+    }
+}
+
+impl RgbStorage for MemoryCache {
+    #[inline]
+    fn has_schema(&self, id: SchemaId) -> Result<bool, CacheError> {
+        Ok(self.schemata.contains_key(&id))
+    }
+
+    #[inline]
+    fn add_schema(&mut self, schema: &Schema) -> Result<bool, CacheError> {
+        Ok(self
+            .schemata
+            .insert(schema.schema_id(), schema.clone())
+            .is_some())
+    }
+}
@@ -13,10 +13,12 @@
 
 mod cache;
 mod file;
+mod memory;
 pub(crate) mod models;
 pub(crate) mod schema;
 mod sql;
 
-pub use cache::{Cache, CacheError};
+pub use cache::{AssetStorage, Cache, CacheError, RgbStorage};
 pub use file::{FileCache, FileCacheConfig, FileCacheError};
+pub use memory::{InMemoryAssetStorage, MemoryCache};
 pub use sql::{SqlCache, SqlCacheConfig, SqlCacheError};
@@ -253,7 +253,7 @@ impl Cache for SqlCache {
     }
 
     fn add_asset(&mut self, asset: Asset) -> Result<bool, CacheError> {
-        let exists = self.assets.insert(*asset.id(), asset).is_some();
+        let exists = self.assets.insert(asset.contract_id(), asset).is_some();
         self.save()?;
         Ok(exists)
     }
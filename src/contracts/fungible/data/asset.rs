@@ -11,13 +11,15 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
+use core::borrow::Borrow;
 use core::convert::{TryFrom, TryInto};
 use core::ops::{Add, AddAssign};
 use diesel::prelude::*;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
 use std::str::FromStr;
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::contracts::fungible::cache::models::{
@@ -28,18 +30,38 @@ use crate::contracts::fungible::cache::SqlCacheError;
 use lnpbp::bitcoin;
 use lnpbp::bitcoin::{OutPoint, Txid};
 use lnpbp::bp;
+use lnpbp::bp::blind::{OutpointHash, OutpointReveal};
+use lnpbp::client_side_validation::Conceal;
 use lnpbp::hashes::Hash;
 use lnpbp::hex::FromHex;
 use lnpbp::rgb::prelude::*;
+use lnpbp::rgb::schema::Schema;
 use lnpbp::rgb::seal::WitnessVoutError;
 use lnpbp::secp256k1zkp::key::SecretKey;
 use lnpbp::secp256k1zkp::Secp256k1;
+use lnpbp::strict_encoding::{self, StrictDecode};
 
 use super::schema::{self, FieldType, OwnedRightsType};
-use crate::error::ServiceErrorDomain;
+use super::DecimalAmount;
+use crate::error::{ParseError, ServiceErrorDomain};
 
 pub type AccountingValue = f32;
 
+/// Rounding applied to floating-point accounting values which can't be
+/// exactly represented with the asset precision
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display)]
+#[display(Debug)]
+pub enum RoundingMode {
+    /// Rounds towards zero
+    Floor,
+    /// Rounds away from zero
+    Ceil,
+    /// Rounds to the nearest atomic value, with halves rounded up
+    HalfUp,
+    /// Does not round, failing on values not representable exactly
+    Reject,
+}
+
 #[derive(
     Clone,
     Copy,
@@ -61,16 +83,88 @@ pub type AccountingValue = f32;
 pub struct AccountingAmount(AtomicValue, u8);
 
 impl AccountingAmount {
+    /// Converts decimal amount into atomic value of an asset with
+    /// `fractional_bits` precision. Fractional digits not fitting the
+    /// precision are truncated; values not fitting into 64-bit integer are
+    /// saturated to `u64::MAX`.
     #[inline]
     pub fn transmutate(
         fractional_bits: u8,
-        accounting_value: AccountingValue,
+        accounting_value: DecimalAmount,
     ) -> AtomicValue {
-        AccountingAmount::from_fractioned_accounting_value(
-            fractional_bits,
-            accounting_value,
-        )
-        .atomic_value()
+        accounting_value
+            .checked_atomic_value(fractional_bits)
+            .unwrap_or(core::u64::MAX)
+    }
+
+    /// Converts floating-point accounting value into atomic value of an asset
+    /// with `fractional_bits` precision, rounding according to `mode`.
+    /// Returns `None` for negative, infinite and NaN values, values not
+    /// fitting into 64-bit integer and, with [`RoundingMode::Reject`], for
+    /// values which would require rounding.
+    pub fn transmutate_with(
+        fractional_bits: u8,
+        accounting_value: AccountingValue,
+        mode: RoundingMode,
+    ) -> Option<AtomicValue> {
+        if !accounting_value.is_finite() || accounting_value < 0.0 {
+            return None;
+        }
+        let scaled =
+            accounting_value as f64 * 10f64.powi(fractional_bits as i32);
+        let atoms = match mode {
+            RoundingMode::Floor => scaled.floor(),
+            RoundingMode::Ceil => scaled.ceil(),
+            RoundingMode::HalfUp => scaled.round(),
+            RoundingMode::Reject if scaled.fract() == 0.0 => scaled,
+            RoundingMode::Reject => return None,
+        };
+        // `u64::MAX as f64` is rounded up to 2^64, which does not fit
+        if atoms >= core::u64::MAX as f64 {
+            None
+        } else {
+            Some(atoms as AtomicValue)
+        }
+    }
+
+    /// Formats `atomic_value` of an asset with given `precision` as a decimal
+    /// string with exactly `precision` fractional digits, like `1234.56780000`
+    /// for precision 8. Inverse of [`AccountingAmount::from_display`] for its
+    /// canonical output.
+    #[inline]
+    pub fn to_display(precision: u8, atomic_value: AtomicValue) -> String {
+        DecimalAmount::from_atomic_value(precision, atomic_value).to_string()
+    }
+
+    /// Parses decimal string into atomic value of an asset with given
+    /// `precision` without floating-point conversion. Unlike
+    /// [`AccountingAmount::transmutate`] fails if the string contains
+    /// non-zero fractional digits not fitting the precision, or if the value
+    /// does not fit into 64-bit integer.
+    pub fn from_display(
+        precision: u8,
+        s: &str,
+    ) -> Result<AtomicValue, ParseError> {
+        let amount = DecimalAmount::from_str(s)?;
+        if amount.exceeds_precision(precision) {
+            return Err(ParseError);
+        }
+        amount.checked_atomic_value(precision).ok_or(ParseError)
+    }
+
+    /// Parses user-entered decimal amount into atomic value of an asset with
+    /// given `precision`, like [`AccountingAmount::from_display`], reporting
+    /// failures as a schema error which names the wrong amount
+    pub fn parse(
+        precision: u8,
+        s: &str,
+    ) -> Result<AtomicValue, ServiceErrorDomain> {
+        Self::from_display(precision, s).map_err(|_| {
+            ServiceErrorDomain::Schema(format!(
+                "Amount '{}' is not a non-negative decimal number representable with precision {}",
+                s, precision
+            ))
+        })
     }
 
     #[inline]
@@ -155,6 +249,11 @@ impl AddAssign for AccountingAmount {
     }
 }
 
+/// Known state of a fungible asset. Strict encoding of the asset is the
+/// concatenation of the strict encodings of its fields in declaration order,
+/// so the field order must be kept stable. Assets encoded before the fields
+/// following `known_allocations` were added are read with
+/// [`Asset::strict_decode_legacy`].
 #[derive(
     Clone, Getters, PartialEq, Debug, Display, StrictEncode, StrictDecode,
 )]
@@ -182,6 +281,39 @@ pub struct Asset {
     unknown_inflation: AccountingAmount,
     /// Specifies outpoints controlling certain amounts of assets
     known_allocations: BTreeMap<bitcoin::OutPoint, Vec<Allocation>>,
+    /// Specifies outpoints controlling non-asset rights (like inflation,
+    /// burn & replace or renomination), which are required to construct
+    /// state transitions spending these rights
+    #[cfg_attr(feature = "serde", serde(default))]
+    known_rights: BTreeMap<bitcoin::OutPoint, Vec<Right>>,
+    /// Specifies blinding data for the allocations which were received to
+    /// the confidential (blinded) seals, so they can be referenced by the
+    /// blinded outpoint hash
+    #[cfg_attr(feature = "serde", serde(default))]
+    known_reveals: BTreeMap<OutpointHash, OutpointReveal>,
+    /// Specifies outpoints controlling allocations with amounts known only
+    /// as Pedersen commitments
+    #[cfg_attr(feature = "serde", serde(default))]
+    known_concealed: BTreeMap<bitcoin::OutPoint, Vec<ConcealedAllocation>>,
+    /// Additional genesis metadata fields (see
+    /// [`FieldType::extra_nomination`]) indexed by the field type
+    #[cfg_attr(feature = "serde", serde(default))]
+    extra_fields: BTreeMap<usize, data::Revealed>,
+    /// Sum of all known burned amounts in atomic units
+    #[cfg_attr(feature = "serde", serde(default))]
+    known_burned: AtomicValue,
+    /// Block height before which the inflation rights can't be exercised
+    #[cfg_attr(feature = "serde", serde(default))]
+    inflation_not_before: Option<u32>,
+    /// Schema of the asset genesis, which is the fungible assets schema or
+    /// a compatible variant of it
+    #[cfg_attr(feature = "serde", serde(default = "default_schema_id"))]
+    schema_id: SchemaId,
+}
+
+#[cfg(feature = "serde")]
+fn default_schema_id() -> SchemaId {
+    schema::schema().schema_id()
 }
 
 impl Asset {
@@ -221,6 +353,16 @@ impl Asset {
             known_inflation: known_inflation,
             unknown_inflation: unknown_inflation,
             known_allocations: read_allocations(&table_value, connection)?,
+            // Rights and seal reveals are not persisted in the database yet
+            known_rights: BTreeMap::new(),
+            known_reveals: BTreeMap::new(),
+            known_concealed: BTreeMap::new(),
+            extra_fields: BTreeMap::new(),
+            known_burned: 0,
+            inflation_not_before: None,
+            // Assets are kept in the database only for the fungible assets
+            // schema
+            schema_id: schema::schema().schema_id(),
         })
     }
 }
@@ -245,6 +387,42 @@ pub struct Allocation {
     value: value::Revealed,
 }
 
+#[derive(
+    Clone, Getters, PartialEq, Debug, Display, StrictEncode, StrictDecode,
+)]
+#[display(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize,),
+    serde(crate = "serde_crate")
+)]
+pub struct ConcealedAllocation {
+    // Unique primary key is `node_id` + `index`
+    node_id: NodeId,
+    /// Index of the assignment of ownership right type within the node
+    index: u16,
+    /// Copy of the outpoint from corresponding entry in
+    /// `Asset::known_concealed`
+    outpoint: bitcoin::OutPoint,
+    commitment: value::Confidential,
+}
+
+impl ConcealedAllocation {
+    /// Converts into allocation with revealed amount, if the provided
+    /// `value` matches the commitment
+    pub fn reveal(&self, value: value::Revealed) -> Option<Allocation> {
+        if value.conceal() != self.commitment {
+            return None;
+        }
+        Some(Allocation {
+            node_id: self.node_id,
+            index: self.index,
+            outpoint: self.outpoint,
+            value,
+        })
+    }
+}
+
 impl Allocation {
     /// Create an Allocation structure by reading the
     /// corresponding Allocation and AllocationUtxo table entries.
@@ -268,6 +446,92 @@ impl Allocation {
             },
         })
     }
+
+    /// Sums atomic values of the `allocations`, saturating at the maximum
+    /// 64-bit value. With `parallel` feature large sets of allocations (see
+    /// [`PARALLEL_SUM_THRESHOLD`]) are summed on the rayon thread pool.
+    pub fn sum<A>(allocations: &[A]) -> AtomicValue
+    where
+        A: Borrow<Allocation> + Sync,
+    {
+        #[cfg(feature = "parallel")]
+        {
+            if allocations.len() >= PARALLEL_SUM_THRESHOLD {
+                use rayon::prelude::*;
+                return allocations
+                    .par_iter()
+                    .map(|alloc| alloc.borrow().value.value)
+                    .reduce(|| 0, AtomicValue::saturating_add);
+            }
+        }
+        Self::sum_sequential(allocations)
+    }
+
+    fn sum_sequential<A>(allocations: &[A]) -> AtomicValue
+    where
+        A: Borrow<Allocation>,
+    {
+        allocations.iter().fold(0u64, |acc, alloc| {
+            acc.saturating_add(alloc.borrow().value.value)
+        })
+    }
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Getters,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Display,
+    StrictEncode,
+    StrictDecode,
+)]
+#[display(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize,),
+    serde(crate = "serde_crate")
+)]
+pub struct Right {
+    // Unique primary key is `node_id` + `right_type` + `index`
+    node_id: NodeId,
+    /// Type of the owned right, matching one of `OwnedRightsType` values
+    right_type: usize,
+    /// Index of the assignment of ownership right type within the node
+    index: u16,
+    /// Copy of the outpoint from corresponding entry in `Asset::known_rights`
+    outpoint: bitcoin::OutPoint,
+}
+
+/// Machine-readable summary of the asset issuance, constructed from the
+/// known asset state with [`Asset::issuance_receipt`]
+#[derive(Clone, Getters, PartialEq, Debug, Display)]
+#[display(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize,),
+    serde(crate = "serde_crate")
+)]
+pub struct IssuanceReceipt {
+    contract_id: ContractId,
+    schema_id: SchemaId,
+    /// Known allocations in atomic units per seal
+    allocations: Vec<(bitcoin::OutPoint, AtomicValue)>,
+    /// Whether the asset supply can be inflated with secondary issues
+    is_inflatable: bool,
+    /// Seals holding known inflation rights
+    reissue_controls: Vec<bitcoin::OutPoint>,
+    /// Seals holding known burn & replace rights
+    burn_controls: Vec<bitcoin::OutPoint>,
+    /// Supply of the primary issue in atomic units
+    issued_supply: AtomicValue,
+    issued_supply_decimal: DecimalAmount,
+    /// Maximum total supply in atomic units
+    max_supply: AtomicValue,
+    max_supply_decimal: DecimalAmount,
 }
 
 #[derive(
@@ -399,10 +663,239 @@ impl Issue {
 }
 
 impl Asset {
+    /// Asset precision, i.e. number of decimal digits after the point in the
+    /// amounts expressed in accounting units
+    #[inline]
+    pub fn precision(&self) -> u8 {
+        self.fractional_bits
+    }
+
+    /// Asset creation (genesis) time
+    #[inline]
+    pub fn created_at(&self) -> DateTime<Utc> {
+        DateTime::from_utc(self.date, Utc)
+    }
+
+    /// Supply issued by the genesis (primary issue) in atomic units
+    pub fn issued_supply(&self) -> AtomicValue {
+        self.known_issues
+            .iter()
+            .find(|issue| issue.is_primary())
+            .map(|issue| issue.amount.atomic_value())
+            .unwrap_or_default()
+    }
+
+    /// Summarizes the asset issuance. Allocations and control seals reflect
+    /// the currently known asset state, which for a newly issued asset
+    /// matches its genesis.
+    pub fn issuance_receipt(&self) -> IssuanceReceipt {
+        let controls = |right_type: OwnedRightsType| {
+            self.known_rights
+                .iter()
+                .filter(|(_, rights)| {
+                    rights.iter().any(|right| right.right_type == *right_type)
+                })
+                .map(|(outpoint, _)| *outpoint)
+                .collect::<Vec<_>>()
+        };
+        let max_supply = self.supply.max_cap.atomic_value();
+        IssuanceReceipt {
+            contract_id: self.id,
+            schema_id: self.schema_id,
+            allocations: self
+                .known_allocations
+                .iter()
+                .map(|(outpoint, allocations)| {
+                    (
+                        *outpoint,
+                        allocations.iter().map(|alloc| alloc.value.value).sum(),
+                    )
+                })
+                .collect(),
+            is_inflatable: !self.known_inflation.is_empty()
+                || self.unknown_inflation.atomic_value() > 0,
+            reissue_controls: controls(OwnedRightsType::Inflation),
+            burn_controls: controls(OwnedRightsType::BurnReplace),
+            issued_supply: self.issued_supply(),
+            issued_supply_decimal: DecimalAmount::from_atomic_value(
+                self.fractional_bits,
+                self.issued_supply(),
+            ),
+            max_supply,
+            max_supply_decimal: DecimalAmount::from_atomic_value(
+                self.fractional_bits,
+                max_supply,
+            ),
+        }
+    }
+
     pub fn add_issue(&self, _issue: Transition) -> Supply {
         unimplemented!()
     }
 
+    /// Applies ticker and/or name changed by a renomination transition
+    pub fn renominate(&mut self, ticker: Option<String>, name: Option<String>) {
+        if let Some(ticker) = ticker {
+            self.ticker = ticker;
+        }
+        if let Some(name) = name {
+            self.name = name;
+        }
+    }
+
+    /// Applies contract text changed by a renomination transition
+    #[inline]
+    pub fn update_description(&mut self, description: String) {
+        self.description = Some(description);
+    }
+
+    /// Id of the asset contract, which is the asset id returned by
+    /// [`Asset::id`]
+    #[inline]
+    pub fn contract_id(&self) -> ContractId {
+        self.id
+    }
+
+    /// Returns value of the additional genesis metadata field, if present
+    #[inline]
+    pub fn field(&self, field_type: FieldType) -> Option<&data::Revealed> {
+        self.extra_fields.get(&*field_type)
+    }
+
+    /// Binary content attached to the asset at the issue, if any
+    pub fn attachment(&self) -> Option<&[u8]> {
+        match self.field(FieldType::Attachment)? {
+            data::Revealed::Bytes(attachment) => Some(attachment),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn rights(&self, seal: &bitcoin::OutPoint) -> Option<&Vec<Right>> {
+        self.known_rights.get(seal)
+    }
+
+    /// Returns the right of the given type assigned to the `seal`, if any
+    pub fn right(
+        &self,
+        seal: &bitcoin::OutPoint,
+        right_type: OwnedRightsType,
+    ) -> Option<&Right> {
+        self.known_rights
+            .get(seal)?
+            .iter()
+            .find(|right| right.right_type == *right_type)
+    }
+
+    pub fn add_right(
+        &mut self,
+        outpoint: bitcoin::OutPoint,
+        node_id: NodeId,
+        right_type: usize,
+        index: u16,
+    ) -> bool {
+        let new_right = Right {
+            node_id,
+            right_type,
+            index,
+            outpoint,
+        };
+        let rights = self.known_rights.entry(outpoint).or_insert(vec![]);
+        if !rights.contains(&new_right) {
+            rights.push(new_right);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn remove_right(&mut self, right: &Right) -> bool {
+        let rights = self.known_rights.entry(right.outpoint).or_insert(vec![]);
+        if let Some(index) = rights.iter().position(|r| r == right) {
+            rights.remove(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Total supply known to be issued by primary and secondary issues in
+    /// atomic units, as declared by their `IssuedSupply` metadata; includes
+    /// the burned part of it
+    pub fn total_supply(&self) -> AtomicValue {
+        self.known_issues.iter().fold(0, |sum, issue| {
+            sum.saturating_add(issue.amount.atomic_value())
+        })
+    }
+
+    /// Sum of the currently known allocations in atomic units. Unlike
+    /// [`Supply::known_circulating`] it does not include allocations owned
+    /// by other parties.
+    #[inline]
+    pub fn known_allocated(&self) -> AtomicValue {
+        self.total_balance()
+    }
+
+    /// Total amount known to be burned in atomic units
+    #[inline]
+    pub fn burned(&self) -> AtomicValue {
+        self.known_burned
+    }
+
+    /// Accounts for `amount` of asset atomic units being burned by reducing
+    /// known circulating supply
+    pub fn register_burn(&mut self, amount: AtomicValue) {
+        self.known_burned = self.known_burned.saturating_add(amount);
+        self.supply.known_circulating =
+            AccountingAmount::from_fractioned_atomic_value(
+                self.fractional_bits,
+                self.supply
+                    .known_circulating
+                    .atomic_value()
+                    .saturating_sub(amount),
+            );
+    }
+
+    /// Accounts for secondary issue of `amount` atomic units by the state
+    /// transition `node_id` spending inflation right on `spent` outpoint,
+    /// which remaining allowance (if any) is passed `forwarded` to a new
+    /// outpoint
+    pub fn register_inflation(
+        &mut self,
+        node_id: NodeId,
+        spent: bitcoin::OutPoint,
+        amount: AtomicValue,
+        forwarded: Option<(bitcoin::OutPoint, AtomicValue)>,
+    ) {
+        self.known_issues.push(Issue {
+            id: node_id,
+            asset_id: self.id,
+            amount: AccountingAmount::from_fractioned_atomic_value(
+                self.fractional_bits,
+                amount,
+            ),
+            origin: Some(spent),
+        });
+        self.supply.known_circulating =
+            AccountingAmount::from_fractioned_atomic_value(
+                self.fractional_bits,
+                self.supply
+                    .known_circulating
+                    .atomic_value()
+                    .saturating_add(amount),
+            );
+        self.known_inflation.remove(&spent);
+        if let Some((outpoint, allowance)) = forwarded {
+            self.known_inflation.insert(
+                outpoint,
+                AccountingAmount::from_fractioned_atomic_value(
+                    self.fractional_bits,
+                    allowance,
+                ),
+            );
+        }
+    }
+
     #[inline]
     pub fn allocations(
         &self,
@@ -411,6 +904,51 @@ impl Asset {
         self.known_allocations.get(seal)
     }
 
+    /// Iterates over all known allocations with revealed amounts without
+    /// cloning them
+    #[inline]
+    pub fn iter_allocations(&self) -> impl Iterator<Item = &Allocation> {
+        self.known_allocations.values().flatten()
+    }
+
+    /// Iterates over the known allocations with revealed amounts assigned to
+    /// the `seal` without cloning them
+    #[inline]
+    pub fn iter_allocations_for<'a>(
+        &'a self,
+        seal: &bitcoin::OutPoint,
+    ) -> impl Iterator<Item = &'a Allocation> {
+        self.known_allocations.get(seal).into_iter().flatten()
+    }
+
+    /// Returns all known allocations with revealed amounts ordered by their
+    /// value, ascending or `descending`. Allocations of the same value are
+    /// ordered by their outpoint (and then by node id and assignment index),
+    /// so the order is reproducible.
+    pub fn allocations_sorted(&self, descending: bool) -> Vec<&Allocation> {
+        let mut allocations = self.iter_allocations().collect::<Vec<_>>();
+        allocations.sort_by(|a, b| {
+            let by_value = if descending {
+                b.value.value.cmp(&a.value.value)
+            } else {
+                a.value.value.cmp(&b.value.value)
+            };
+            by_value
+                .then_with(|| a.outpoint.cmp(&b.outpoint))
+                .then_with(|| a.node_id.cmp(&b.node_id))
+                .then_with(|| a.index.cmp(&b.index))
+        });
+        allocations
+    }
+
+    /// Returns known allocations with revealed amounts of at least `min`
+    /// atomic units, ordered by their outpoint
+    pub fn allocations_above(&self, min: AtomicValue) -> Vec<&Allocation> {
+        self.iter_allocations()
+            .filter(|alloc| alloc.value.value >= min)
+            .collect()
+    }
+
     pub fn add_allocation(
         &mut self,
         outpoint: bitcoin::OutPoint,
@@ -434,6 +972,135 @@ impl Asset {
         }
     }
 
+    /// Returns sum of atomic asset values allocated to the given `seals`.
+    /// Only allocations with revealed amounts are known to the asset, so
+    /// confidential allocations are not accounted for.
+    pub fn balance(&self, seals: &[bitcoin::OutPoint]) -> AtomicValue {
+        seals
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .flat_map(|seal| self.iter_allocations_for(seal))
+            .fold(0u64, |acc, alloc| acc.saturating_add(alloc.value.value))
+    }
+
+    /// Returns sum of atomic asset values across all known allocations
+    pub fn total_balance(&self) -> AtomicValue {
+        Allocation::sum(&self.iter_allocations().collect::<Vec<_>>())
+    }
+
+    /// Returns sum of atomic asset values of the known allocations per each
+    /// seal holding any of them
+    pub fn consolidate(&self) -> BTreeMap<bitcoin::OutPoint, AtomicValue> {
+        self.known_allocations
+            .iter()
+            .filter(|(_, allocations)| !allocations.is_empty())
+            .map(|(seal, allocations)| {
+                (
+                    *seal,
+                    allocations.iter().fold(0u64, |acc, alloc| {
+                        acc.saturating_add(alloc.value.value)
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns distinct seals holding at least one of the known allocations,
+    /// ordered by outpoint
+    pub fn outpoints(&self) -> Vec<bitcoin::OutPoint> {
+        self.known_allocations
+            .iter()
+            .filter(|(_, allocations)| !allocations.is_empty())
+            .map(|(seal, _)| *seal)
+            .collect()
+    }
+
+    /// Returns distinct seals holding at least one of the known allocations
+    /// together with the sum of their atomic values, ordered by outpoint
+    #[inline]
+    pub fn outpoints_with_amounts(
+        &self,
+    ) -> Vec<(bitcoin::OutPoint, AtomicValue)> {
+        self.consolidate().into_iter().collect()
+    }
+
+    /// Returns sum of all known allocations in accounting units of the asset
+    #[inline]
+    pub fn balance_decimal(&self) -> DecimalAmount {
+        DecimalAmount::from_atomic_value(
+            self.fractional_bits,
+            self.total_balance(),
+        )
+    }
+
+    /// Adds allocation received to a confidential seal, keeping its blinding
+    /// data, so the allocation can be later referenced by the blinded outpoint
+    /// hash
+    pub fn add_confidential_allocation(
+        &mut self,
+        reveal: OutpointReveal,
+        node_id: NodeId,
+        index: u16,
+        value: value::Revealed,
+    ) -> bool {
+        self.known_reveals.insert(reveal.conceal(), reveal.clone());
+        self.add_allocation(reveal.into(), node_id, index, value)
+    }
+
+    /// Keeps blinding data of our seal, so allocations assigned to it by
+    /// transitions may be added with [`Asset::apply_transition`]. Returns
+    /// `false` if the blinding data were already known.
+    pub fn add_reveal(&mut self, reveal: OutpointReveal) -> bool {
+        self.known_reveals
+            .insert(reveal.conceal(), reveal)
+            .is_none()
+    }
+
+    /// Resolves blinded outpoint hash into the outpoint, if the blinding data
+    /// for it are known
+    #[inline]
+    pub fn reveal_seal(
+        &self,
+        seal_confidential: &OutpointHash,
+    ) -> Option<bitcoin::OutPoint> {
+        self.known_reveals
+            .get(seal_confidential)
+            .map(|reveal| reveal.clone().into())
+    }
+
+    #[inline]
+    pub fn concealed_allocations(
+        &self,
+        seal: &bitcoin::OutPoint,
+    ) -> Option<&Vec<ConcealedAllocation>> {
+        self.known_concealed.get(seal)
+    }
+
+    /// Adds allocation which amount is known only as a Pedersen commitment
+    pub fn add_concealed_allocation(
+        &mut self,
+        outpoint: bitcoin::OutPoint,
+        node_id: NodeId,
+        index: u16,
+        commitment: value::Confidential,
+    ) -> bool {
+        let new_allocation = ConcealedAllocation {
+            node_id,
+            index,
+            outpoint,
+            commitment,
+        };
+        let allocations =
+            self.known_concealed.entry(outpoint).or_insert(vec![]);
+        if !allocations.contains(&new_allocation) {
+            allocations.push(new_allocation);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn remove_allocation(
         &mut self,
         outpoint: bitcoin::OutPoint,
@@ -458,6 +1125,294 @@ impl Asset {
             false
         }
     }
+
+    /// Drops allocations, both with revealed and concealed amounts, assigned
+    /// to the `spent` seals, which were consumed by confirmed transitions.
+    /// Allocations on other seals are never removed. Returns the number of
+    /// the removed allocations.
+    pub fn prune_spent(&mut self, spent: &[bitcoin::OutPoint]) -> usize {
+        spent
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|seal| {
+                self.known_allocations
+                    .remove(seal)
+                    .map(|allocations| allocations.len())
+                    .unwrap_or_default()
+                    + self
+                        .known_concealed
+                        .remove(seal)
+                        .map(|allocations| allocations.len())
+                        .unwrap_or_default()
+            })
+            .sum()
+    }
+
+    /// Drops allocations assigned by the given nodes, which outputs are known
+    /// to be spent by confirmed transitions; allocations assigned by other
+    /// nodes are never removed. Returns the number of the removed
+    /// allocations.
+    pub fn prune_nodes(&mut self, node_ids: &BTreeSet<NodeId>) -> usize {
+        let mut pruned = 0usize;
+        for allocations in self.known_allocations.values_mut() {
+            let len = allocations.len();
+            allocations.retain(|alloc| !node_ids.contains(&alloc.node_id));
+            pruned += len - allocations.len();
+        }
+        for allocations in self.known_concealed.values_mut() {
+            let len = allocations.len();
+            allocations.retain(|alloc| !node_ids.contains(&alloc.node_id));
+            pruned += len - allocations.len();
+        }
+        self.known_allocations
+            .retain(|_, allocations| !allocations.is_empty());
+        self.known_concealed
+            .retain(|_, allocations| !allocations.is_empty());
+        pruned
+    }
+
+    /// Updates known asset state with a transition once its witness
+    /// transaction is confirmed: removes allocations and rights spent by the
+    /// transition (per its parent owned rights) and adds the new allocations
+    /// with revealed amounts assigned to revealed transaction outpoints or to
+    /// blinded seals which blinding data are known to the asset, as well as
+    /// the new rights assigned to revealed transaction outpoints. Allocations
+    /// and rights on the witness transaction outputs and on other blinded
+    /// seals are not added, since their outpoints are not known.
+    pub fn apply_transition(&mut self, transition: &Transition) {
+        for (parent_id, parent_rights) in transition.parent_owned_rights() {
+            for (right_type, indexes) in parent_rights {
+                let spent = |node_id: &NodeId, index: &u16| {
+                    node_id == parent_id && indexes.contains(index)
+                };
+                if *right_type == *OwnedRightsType::Assets {
+                    for allocations in self.known_allocations.values_mut() {
+                        allocations.retain(|alloc| {
+                            !spent(&alloc.node_id, &alloc.index)
+                        });
+                    }
+                    for allocations in self.known_concealed.values_mut() {
+                        allocations.retain(|alloc| {
+                            !spent(&alloc.node_id, &alloc.index)
+                        });
+                    }
+                } else {
+                    for rights in self.known_rights.values_mut() {
+                        rights.retain(|right| {
+                            right.right_type != *right_type
+                                || !spent(&right.node_id, &right.index)
+                        });
+                    }
+                }
+            }
+        }
+
+        let node_id = transition.node_id();
+        for assignments in
+            transition.owned_rights_by_type(*OwnedRightsType::Assets)
+        {
+            for (index, state) in
+                assignments.to_discrete_state().into_iter().enumerate()
+            {
+                match state {
+                    OwnedState::Revealed {
+                        seal_definition:
+                            seal::Revealed::TxOutpoint(outpoint_reveal),
+                        assigned_state,
+                    } => {
+                        self.add_allocation(
+                            outpoint_reveal.into(),
+                            node_id,
+                            index as u16,
+                            assigned_state,
+                        );
+                    }
+                    OwnedState::ConfidentialSeal {
+                        seal_definition,
+                        assigned_state,
+                    } => {
+                        if let Some(outpoint) =
+                            self.reveal_seal(&seal_definition)
+                        {
+                            self.add_allocation(
+                                outpoint,
+                                node_id,
+                                index as u16,
+                                assigned_state,
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for (right_type, assignments) in transition.owned_rights() {
+            if *right_type == *OwnedRightsType::Assets {
+                continue;
+            }
+            let seals = match assignments {
+                Assignments::Declarative(states) => states
+                    .clone()
+                    .into_iter()
+                    .map(|state| match state {
+                        OwnedState::Revealed {
+                            seal_definition, ..
+                        } => Some(seal_definition),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                Assignments::CustomData(states) => states
+                    .clone()
+                    .into_iter()
+                    .map(|state| match state {
+                        OwnedState::Revealed {
+                            seal_definition, ..
+                        } => Some(seal_definition),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                Assignments::DiscreteFiniteField(_) => vec![],
+            };
+            for (index, seal) in seals.into_iter().enumerate() {
+                if let Some(seal::Revealed::TxOutpoint(outpoint_reveal)) = seal
+                {
+                    self.add_right(
+                        outpoint_reveal.into(),
+                        node_id,
+                        *right_type,
+                        index as u16,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Version of the JSON format of the asset state produced by
+/// [`Asset::to_json`]
+pub const ASSET_JSON_VERSION: u16 = 1;
+
+/// Minimal number of allocations summed in parallel by [`Allocation::sum`]
+/// with `parallel` feature. Adding 64-bit integers is much cheaper than
+/// splitting the work across the rayon thread pool, so below tens of
+/// thousands of allocations sequential summation is faster.
+pub const PARALLEL_SUM_THRESHOLD: usize = 32_768;
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(crate = "serde_crate")]
+struct AssetJsonExport<'a> {
+    version: u16,
+    asset: &'a Asset,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(crate = "serde_crate")]
+struct AssetJsonImport {
+    version: u16,
+    // Parsed only after the version check
+    asset: serde_json::Value,
+}
+
+#[cfg(feature = "serde")]
+impl Asset {
+    /// Exports the known asset state as JSON tagged with
+    /// [`ASSET_JSON_VERSION`]. With `redact_confidential` the blinding data
+    /// of the seals received to blinded outpoints and the allocations with
+    /// concealed amounts are omitted; blinding factors of the revealed
+    /// amounts are always kept, since they are required to spend the
+    /// allocations.
+    pub fn to_json(
+        &self,
+        redact_confidential: bool,
+    ) -> Result<String, ServiceErrorDomain> {
+        let redacted;
+        let asset = if redact_confidential {
+            let mut asset = self.clone();
+            asset.known_reveals.clear();
+            asset.known_concealed.clear();
+            redacted = asset;
+            &redacted
+        } else {
+            self
+        };
+        serde_json::to_string(&AssetJsonExport {
+            version: ASSET_JSON_VERSION,
+            asset,
+        })
+        .map_err(|err| {
+            ServiceErrorDomain::Schema(format!(
+                "Can't export asset state: {}",
+                err
+            ))
+        })
+    }
+
+    /// Imports asset state exported with [`Asset::to_json`], rejecting
+    /// unknown format versions
+    pub fn from_json(json: &str) -> Result<Self, ServiceErrorDomain> {
+        let err = |err: serde_json::Error| {
+            ServiceErrorDomain::Schema(format!(
+                "Can't import asset state: {}",
+                err
+            ))
+        };
+        let import: AssetJsonImport =
+            serde_json::from_str(json).map_err(err)?;
+        if import.version != ASSET_JSON_VERSION {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Unsupported asset state format version {}",
+                import.version
+            )))?
+        }
+        serde_json::from_value(import.asset).map_err(err)
+    }
+}
+
+impl Asset {
+    /// Constructs asset from the `genesis` supplied by other party, checking
+    /// that it is committed to the fungible assets schema and does not carry
+    /// metadata fields or owned rights unknown to the schema
+    #[inline]
+    pub fn import(genesis: Genesis) -> Result<Asset, ServiceErrorDomain> {
+        Asset::import_with_schema(genesis, &schema::schema())
+    }
+
+    /// Constructs asset from the `genesis` supplied by other party like
+    /// [`Asset::import`], checking it against a compatible `schema` variant
+    pub fn import_with_schema(
+        genesis: Genesis,
+        schema: &Schema,
+    ) -> Result<Asset, ServiceErrorDomain> {
+        if genesis.schema_id() != schema.schema_id() {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Genesis schema {} does not match fungible assets schema {}",
+                genesis.schema_id(),
+                schema.schema_id()
+            )))?
+        }
+        for field_type in genesis.metadata().keys() {
+            if !schema.genesis.metadata.contains_key(field_type) {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Genesis contains field {} not defined by the schema",
+                    field_type
+                )))?
+            }
+        }
+        for right_type in genesis.owned_rights().keys() {
+            if !schema.genesis.owned_rights.contains_key(right_type) {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Genesis contains owned rights {} not defined by the schema",
+                    right_type
+                )))?
+            }
+        }
+
+        Ok(Asset::from_genesis(genesis, schema.schema_id())?)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, From, Error)]
@@ -472,6 +1427,9 @@ pub enum Error {
     /// can't be a witness transaction for genesis
     #[from(WitnessVoutError)]
     GenesisSeal,
+
+    /// Genesis timestamp {_0} is out of the supported date range
+    Timestamp(i64),
 }
 
 impl From<Error> for ServiceErrorDomain {
@@ -480,11 +1438,56 @@ impl From<Error> for ServiceErrorDomain {
     }
 }
 
+impl Asset {
+    /// Decodes asset strict-encoded with the original layout, which ends with
+    /// `known_allocations`. The rights, seal reveals, concealed allocations
+    /// and extra genesis fields are not known for such assets and are left
+    /// empty, while the asset is assumed to use the fungible assets schema.
+    pub fn strict_decode_legacy<D: io::Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        Ok(Self {
+            id: StrictDecode::strict_decode(&mut d)?,
+            ticker: StrictDecode::strict_decode(&mut d)?,
+            name: StrictDecode::strict_decode(&mut d)?,
+            description: StrictDecode::strict_decode(&mut d)?,
+            supply: StrictDecode::strict_decode(&mut d)?,
+            chain: StrictDecode::strict_decode(&mut d)?,
+            fractional_bits: StrictDecode::strict_decode(&mut d)?,
+            date: StrictDecode::strict_decode(&mut d)?,
+            known_issues: StrictDecode::strict_decode(&mut d)?,
+            known_inflation: StrictDecode::strict_decode(&mut d)?,
+            unknown_inflation: StrictDecode::strict_decode(&mut d)?,
+            known_allocations: StrictDecode::strict_decode(&mut d)?,
+            known_rights: BTreeMap::new(),
+            known_reveals: BTreeMap::new(),
+            known_concealed: BTreeMap::new(),
+            extra_fields: BTreeMap::new(),
+            known_burned: 0,
+            inflation_not_before: None,
+            schema_id: schema::schema().schema_id(),
+        })
+    }
+}
+
 impl TryFrom<Genesis> for Asset {
     type Error = Error;
 
+    #[inline]
     fn try_from(genesis: Genesis) -> Result<Self, Self::Error> {
-        if genesis.schema_id() != schema::schema().schema_id() {
+        Asset::from_genesis(genesis, schema::schema().schema_id())
+    }
+}
+
+impl Asset {
+    /// Constructs asset from the `genesis` committed to the schema with
+    /// `schema_id`, which must be compatible with the fungible assets schema
+    /// (see [`schema::check_compatibility`])
+    pub fn from_genesis(
+        genesis: Genesis,
+        schema_id: SchemaId,
+    ) -> Result<Self, Error> {
+        if genesis.schema_id() != schema_id {
             Err(schema::Error::WrongSchemaId)?;
         }
         let genesis_meta = genesis.metadata();
@@ -499,6 +1502,10 @@ impl TryFrom<Genesis> for Asset {
                 .first()
                 .ok_or(schema::Error::NotAllFieldsPresent)?,
         );
+        let timestamp = *genesis_meta
+            .i64(*FieldType::Timestamp)
+            .first()
+            .ok_or(schema::Error::NotAllFieldsPresent)?;
         let mut known_inflation = BTreeMap::<_, _>::default();
         let mut unknown_inflation = AccountingAmount::default();
 
@@ -577,6 +1584,50 @@ impl TryFrom<Genesis> for Asset {
                     }
                 });
         }
+        let mut known_rights =
+            BTreeMap::<bitcoin::OutPoint, Vec<Right>>::default();
+        for (right_type, assignments) in genesis.owned_rights() {
+            if *right_type == *OwnedRightsType::Assets {
+                continue;
+            }
+            let seals = match assignments {
+                Assignments::Declarative(_) => assignments
+                    .to_declarative_state()
+                    .into_iter()
+                    .map(|state| match state {
+                        OwnedState::Revealed {
+                            seal_definition, ..
+                        } => Some(seal_definition),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                Assignments::CustomData(_) => assignments
+                    .to_custom_state()
+                    .into_iter()
+                    .map(|state| match state {
+                        OwnedState::Revealed {
+                            seal_definition, ..
+                        } => Some(seal_definition),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                Assignments::DiscreteFiniteField(_) => vec![],
+            };
+            for (index, seal) in seals.into_iter().enumerate() {
+                if let Some(seal::Revealed::TxOutpoint(outpoint_reveal)) = seal
+                {
+                    known_rights
+                        .entry(outpoint_reveal.clone().into())
+                        .or_insert(vec![])
+                        .push(Right {
+                            node_id,
+                            right_type: *right_type,
+                            index: index as u16,
+                            outpoint: outpoint_reveal.into(),
+                        })
+                }
+            }
+        }
         Ok(Self {
             id: genesis.contract_id(),
             chain: genesis.chain().clone(),
@@ -600,6 +1651,8 @@ impl TryFrom<Genesis> for Asset {
                 max_cap: genesis
                     .owned_rights_by_type(*OwnedRightsType::Inflation)
                     .map(|assignments| {
+                        // Max cap is the primary issue plus everything which
+                        // still can be issued with known inflation rights
                         AccountingAmount::from_fractioned_atomic_value(
                             fractional_bits,
                             assignments
@@ -609,25 +1662,536 @@ impl TryFrom<Genesis> for Asset {
                                     data::Revealed::U64(cap) => *cap,
                                     _ => 0,
                                 })
-                                .sum(),
+                                .fold(supply.atomic_value(), |acc, cap| {
+                                    acc.saturating_add(cap)
+                                }),
                         )
                     })
                     .unwrap_or(supply),
             },
             fractional_bits,
-            date: NaiveDateTime::from_timestamp(
-                *genesis_meta
-                    .i64(*FieldType::Timestamp)
-                    .first()
-                    .ok_or(schema::Error::NotAllFieldsPresent)?,
-                0,
-            ),
+            date: NaiveDateTime::from_timestamp_opt(timestamp, 0)
+                .ok_or(Error::Timestamp(timestamp))?,
             known_inflation,
             unknown_inflation,
             known_issues: vec![issue],
             // we assume that each genesis allocation with revealed amount
             // and known seal (they are always revealed together) belongs to us
             known_allocations,
+            known_rights,
+            known_reveals: BTreeMap::new(),
+            known_concealed: BTreeMap::new(),
+            extra_fields: FieldType::extra_nomination()
+                .into_iter()
+                .filter_map(|field_type| {
+                    genesis_meta
+                        .get(&*field_type)?
+                        .iter()
+                        .next()
+                        .map(|value| (*field_type, value.clone()))
+                })
+                .collect(),
+            known_burned: 0,
+            inflation_not_before: genesis_meta
+                .u32(*FieldType::InflationNotBefore)
+                .first()
+                .copied(),
+            schema_id,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lnpbp::bp::blind::OutpointReveal;
+    use lnpbp::client_side_validation::Conceal;
+
+    fn txid() -> Txid {
+        Txid::from_hex(
+            "db2f3035e05795d72e2744dc0e88b2f72acbed97ee9a54c2c7f52d426ae05627",
+        )
+        .unwrap()
+    }
+
+    fn outpoint(vout: u32) -> OutPoint {
+        OutPoint { txid: txid(), vout }
+    }
+
+    fn reveal(vout: u32) -> OutpointReveal {
+        OutpointReveal {
+            blinding: vout as u64,
+            txid: txid(),
+            vout,
+        }
+    }
+
+    /// Constructs asset with allocations of 2 and 3 units on the first
+    /// outpoint, 4 units on the second one and confidential allocation of 5
+    /// units
+    fn asset() -> Asset {
+        asset_at(1602340666).unwrap()
+    }
+
+    fn asset_at(timestamp: i64) -> Result<Asset, Error> {
+        Asset::try_from(genesis_at(timestamp))
+    }
+
+    fn genesis_at(timestamp: i64) -> Genesis {
+        let metadata = type_map! {
+            FieldType::Ticker => field!(String, s!("TICK")),
+            FieldType::Name => field!(String, s!("Test asset")),
+            FieldType::Precision => field!(U8, 2),
+            FieldType::Timestamp => field!(I64, timestamp),
+            FieldType::IssuedSupply => field!(U64, 14)
+        };
+        let blinding =
+            SecretKey::from_slice(&Secp256k1::new(), &[1u8; 32]).unwrap();
+        let owned_rights = type_map! {
+            OwnedRightsType::Assets => Assignments::zero_balanced(
+                vec![value::Revealed { value: 14, blinding }],
+                vec![
+                    (seal::Revealed::TxOutpoint(reveal(0)), 2),
+                    (seal::Revealed::TxOutpoint(reveal(0)), 3),
+                    (seal::Revealed::TxOutpoint(reveal(1)), 4),
+                ],
+                vec![(reveal(2).conceal(), 5)],
+            )
+        };
+        Genesis::with(
+            schema::schema().schema_id(),
+            bp::Chain::Testnet3,
+            metadata.into(),
+            owned_rights,
+            bset![],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_balance() {
+        let asset = asset();
+        assert_eq!(asset.balance(&[outpoint(0)]), 5);
+        assert_eq!(asset.balance(&[outpoint(0), outpoint(1)]), 9);
+        // Duplicated seals must not be accounted twice
+        assert_eq!(asset.balance(&[outpoint(1), outpoint(1)]), 4);
+        // Confidential allocation is unknown to the asset
+        assert_eq!(asset.balance(&[outpoint(2)]), 0);
+        assert_eq!(asset.total_balance(), 9);
+        assert_eq!(asset.balance_decimal().to_string(), "0.09");
+    }
+
+    #[test]
+    fn test_supply_accessors() {
+        let mut asset = asset();
+        let node_id = *asset.allocations(&outpoint(0)).unwrap()[0].node_id();
+        assert_eq!(asset.total_supply(), 14);
+        assert_eq!(asset.known_allocated(), 9);
+        assert_eq!(asset.burned(), 0);
+
+        // Simulating transfer of the allocation of 4 units to a new seal
+        // with change of 1 unit, followed by a burn of 2 units
+        let alloc = asset.allocations(&outpoint(1)).unwrap()[0].clone();
+        asset.remove_allocation(
+            outpoint(1),
+            *alloc.node_id(),
+            *alloc.index(),
+            alloc.value().clone(),
+        );
+        asset.add_allocation(
+            outpoint(3),
+            node_id,
+            0,
+            value::Revealed {
+                value: 1,
+                blinding: alloc.value().blinding.clone(),
+            },
+        );
+        asset.register_burn(2);
+        assert_eq!(asset.known_allocated(), 6);
+        assert_eq!(asset.burned(), 2);
+        assert_eq!(asset.total_supply(), 14);
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 12);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for (precision, atomic_value, display) in &[
+            (0u8, 1234u64, "1234"),
+            (0, core::u64::MAX, "18446744073709551615"),
+            (2, 123456, "1234.56"),
+            (2, 5, "0.05"),
+            (8, 123456780000, "1234.56780000"),
+            (18, 1, "0.000000000000000001"),
+            (18, core::u64::MAX, "18.446744073709551615"),
+        ] {
+            assert_eq!(
+                AccountingAmount::to_display(*precision, *atomic_value),
+                *display
+            );
+            assert_eq!(
+                AccountingAmount::from_display(*precision, display).unwrap(),
+                *atomic_value
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_display_exact() {
+        assert_eq!(AccountingAmount::from_display(2, "12").unwrap(), 1200);
+        assert_eq!(AccountingAmount::from_display(2, "12.500").unwrap(), 1250);
+        assert!(AccountingAmount::from_display(2, "12.505").is_err());
+        assert!(AccountingAmount::from_display(0, "0.1").is_err());
+        assert!(AccountingAmount::from_display(18, "18.446744073709551616")
+            .is_err());
+        assert!(AccountingAmount::from_display(2, "1.2e3").is_err());
+    }
+
+    #[test]
+    fn test_transmutate_rounding() {
+        use RoundingMode::*;
+
+        assert_eq!(AccountingAmount::transmutate_with(2, 0.1, Reject), None);
+        assert_eq!(AccountingAmount::transmutate_with(2, 0.1, Floor), Some(10));
+        assert_eq!(AccountingAmount::transmutate_with(2, 0.1, Ceil), Some(11));
+        assert_eq!(
+            AccountingAmount::transmutate_with(2, 0.1, HalfUp),
+            Some(10)
+        );
+        assert_eq!(
+            AccountingAmount::transmutate_with(1, 0.25, HalfUp),
+            Some(3)
+        );
+        assert_eq!(
+            AccountingAmount::transmutate_with(2, 0.5, Reject),
+            Some(50)
+        );
+        assert_eq!(AccountingAmount::transmutate_with(0, -1.0, Floor), None);
+        assert_eq!(AccountingAmount::transmutate_with(18, 1e20, Floor), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(AccountingAmount::parse(2, "0.001").is_err());
+        assert_eq!(AccountingAmount::parse(2, "0.01").unwrap(), 1);
+        assert_eq!(
+            AccountingAmount::parse(8, "1000000000").unwrap(),
+            100_000_000_000_000_000
+        );
+        assert!(AccountingAmount::parse(8, "-1").is_err());
+        assert!(AccountingAmount::parse(0, "-0").is_err());
+        assert!(matches!(
+            AccountingAmount::parse(2, "12.5x"),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_created_at() {
+        assert_eq!(asset().created_at().timestamp(), 1602340666);
+        assert_eq!(
+            asset_at(1602340667).unwrap().created_at().timestamp(),
+            1602340667
+        );
+        assert_eq!(
+            asset_at(core::i64::MAX).unwrap_err(),
+            Error::Timestamp(core::i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_display_precision() {
+        assert_eq!(AccountingAmount::to_display(2, 1250), "12.50");
+        assert_eq!(AccountingAmount::to_display(0, 1250), "1250");
+        assert_eq!(AccountingAmount::to_display(8, 1), "0.00000001");
+        assert_eq!(
+            AccountingAmount::to_display(0, core::u64::MAX),
+            "18446744073709551615"
+        );
+        assert_eq!(
+            AccountingAmount::to_display(19, core::u64::MAX),
+            "1.8446744073709551615"
+        );
+        assert_eq!(
+            AccountingAmount::to_display(20, core::u64::MAX),
+            "0.18446744073709551615"
+        );
+
+        for (precision, s) in &[
+            (0u8, "0"),
+            (0, "18446744073709551615"),
+            (2, "12.50"),
+            (2, "0.01"),
+            (8, "21000000.00000000"),
+            (18, "18.446744073709551615"),
+        ] {
+            let atoms = AccountingAmount::parse(*precision, s).unwrap();
+            assert_eq!(AccountingAmount::to_display(*precision, atoms), *s);
+        }
+    }
+
+    #[test]
+    fn test_iter_allocations() {
+        let mut asset = asset();
+        let template = asset.allocations(&outpoint(0)).unwrap()[0].clone();
+        let known = asset.iter_allocations().count();
+        for vout in 3..1003u32 {
+            asset.add_allocation(
+                outpoint(vout),
+                template.node_id,
+                vout as u16,
+                value::Revealed {
+                    value: vout as AtomicValue,
+                    blinding: template.value.blinding.clone(),
+                },
+            );
+        }
+
+        // Summing over borrowed allocations gives the same result as over
+        // the cloned ones
+        let cloned = asset
+            .known_allocations
+            .values()
+            .cloned()
+            .flatten()
+            .fold(0u64, |acc, alloc| acc + alloc.value.value);
+        let borrowed = asset
+            .iter_allocations()
+            .fold(0u64, |acc, alloc| acc + alloc.value.value);
+        assert_eq!(borrowed, cloned);
+        assert_eq!(borrowed, asset.total_balance());
+        assert_eq!(asset.iter_allocations().count(), known + 1000);
+
+        assert_eq!(asset.iter_allocations_for(&outpoint(0)).count(), 2);
+        assert_eq!(asset.iter_allocations_for(&outpoint(2)).count(), 0);
+        assert_eq!(
+            asset
+                .iter_allocations_for(&outpoint(500))
+                .map(|alloc| alloc.value.value)
+                .collect::<Vec<_>>(),
+            vec![500]
+        );
+    }
+
+    #[test]
+    fn test_consolidate() {
+        assert_eq!(
+            asset().consolidate(),
+            bmap! { outpoint(0) => 5u64, outpoint(1) => 4u64 }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_json_round_trip() {
+        let mut asset = asset();
+        let node_id = *asset.allocations(&outpoint(0)).unwrap()[0].node_id();
+        let value = asset.allocations(&outpoint(0)).unwrap()[0].value().clone();
+        asset.add_confidential_allocation(reveal(3), node_id, 5, value);
+
+        let json = asset.to_json(false).unwrap();
+        assert_eq!(Asset::from_json(&json).unwrap(), asset);
+
+        let redacted = Asset::from_json(&asset.to_json(true).unwrap()).unwrap();
+        assert_eq!(redacted.reveal_seal(&reveal(3).conceal()), None);
+        assert_eq!(asset.reveal_seal(&reveal(3).conceal()), Some(outpoint(3)));
+        assert_eq!(
+            redacted.allocations(&outpoint(0)),
+            asset.allocations(&outpoint(0))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_json_unknown_version() {
+        let json = asset().to_json(false).unwrap();
+        let json = json.replacen(
+            &format!("\"version\":{}", ASSET_JSON_VERSION),
+            "\"version\":2",
+            1,
+        );
+        assert!(matches!(
+            Asset::from_json(&json),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        // Format version is checked before the state itself, which may
+        // change in the future versions
+        assert!(matches!(
+            Asset::from_json("{\"version\":2,\"asset\":{\"unknown\":0}}"),
+            Err(ServiceErrorDomain::Schema(ref msg)) if msg.contains("version 2")
+        ));
+    }
+
+    #[test]
+    fn test_strict_encoding() {
+        use lnpbp::strict_encoding::{strict_encode, StrictDecode};
+
+        let mut asset = asset();
+        let allocation = asset.allocations(&outpoint(0)).unwrap()[0].clone();
+        asset.add_confidential_allocation(
+            reveal(3),
+            *allocation.node_id(),
+            5,
+            allocation.value().clone(),
+        );
+        asset.add_concealed_allocation(
+            outpoint(4),
+            *allocation.node_id(),
+            6,
+            allocation.value().conceal(),
+        );
+
+        let data = strict_encode(&asset).unwrap();
+        assert_eq!(Asset::strict_decode(&data[..]).unwrap(), asset);
+        let data = strict_encode(&allocation).unwrap();
+        assert_eq!(Allocation::strict_decode(&data[..]).unwrap(), allocation);
+    }
+
+    #[test]
+    fn test_import() {
+        let genesis = genesis_at(1602340666);
+        let asset = Asset::import(genesis.clone()).unwrap();
+        assert_eq!(asset, Asset::try_from(genesis).unwrap());
+        assert_eq!(asset.total_balance(), 9);
+    }
+
+    #[test]
+    fn test_import_foreign_schema() {
+        let genesis = genesis_at(1602340666);
+        let foreign = Genesis::with(
+            schema::schema_v2().schema_id(),
+            bp::Chain::Testnet3,
+            genesis.metadata().clone(),
+            genesis.owned_rights().clone(),
+            bset![],
+            vec![],
+        );
+        assert!(matches!(
+            Asset::import(foreign),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_allocations_sorted() {
+        let mut asset = asset();
+        let template = asset.allocations(&outpoint(0)).unwrap()[0].clone();
+        // Allocation of the same value as the one on the second outpoint
+        asset.add_allocation(
+            outpoint(3),
+            template.node_id,
+            3,
+            value::Revealed {
+                value: 4,
+                blinding: template.value.blinding.clone(),
+            },
+        );
+        let summary = |allocations: Vec<&Allocation>| {
+            allocations
+                .into_iter()
+                .map(|alloc| (alloc.outpoint.vout, alloc.value.value))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            summary(asset.allocations_sorted(false)),
+            vec![(0, 2), (0, 3), (1, 4), (3, 4)]
+        );
+        assert_eq!(
+            summary(asset.allocations_sorted(true)),
+            vec![(1, 4), (3, 4), (0, 3), (0, 2)]
+        );
+        assert_eq!(
+            summary(asset.allocations_above(3)),
+            vec![(0, 3), (1, 4), (3, 4)]
+        );
+        assert!(asset.allocations_above(5).is_empty());
+        assert_eq!(asset.allocations_above(0).len(), 4);
+    }
+
+    #[test]
+    fn test_allocation_sum() {
+        let template = asset().allocations(&outpoint(0)).unwrap()[0].clone();
+        let allocations = (0..PARALLEL_SUM_THRESHOLD as u32 * 3)
+            .map(|no| Allocation {
+                node_id: template.node_id,
+                index: no as u16,
+                outpoint: outpoint(no),
+                value: value::Revealed {
+                    value: no as AtomicValue * 7,
+                    blinding: template.value.blinding.clone(),
+                },
+            })
+            .collect::<Vec<_>>();
+        let expected = allocations
+            .iter()
+            .fold(0u64, |acc, alloc| acc + alloc.value.value);
+
+        // Large set is summed in parallel with `parallel` feature, while the
+        // small one is always summed sequentially
+        assert_eq!(Allocation::sum(&allocations), expected);
+        assert_eq!(Allocation::sum_sequential(&allocations), expected);
+        assert_eq!(Allocation::sum(&allocations[..10]), 315);
+
+        let refs = allocations.iter().collect::<Vec<_>>();
+        assert_eq!(Allocation::sum(&refs), expected);
+
+        let mut saturating = allocations[..2].to_vec();
+        saturating[1].value.value = core::u64::MAX;
+        assert_eq!(Allocation::sum(&saturating), core::u64::MAX);
+    }
+
+    #[test]
+    fn test_outpoints() {
+        let mut asset = asset();
+        let template = asset.allocations(&outpoint(0)).unwrap()[0].clone();
+        asset.add_allocation(
+            outpoint(3),
+            template.node_id,
+            3,
+            value::Revealed {
+                value: 7,
+                blinding: template.value.blinding.clone(),
+            },
+        );
+
+        assert_eq!(
+            asset.outpoints(),
+            vec![outpoint(0), outpoint(1), outpoint(3)]
+        );
+        assert_eq!(
+            asset.outpoints_with_amounts(),
+            vec![(outpoint(0), 5), (outpoint(1), 4), (outpoint(3), 7)]
+        );
+    }
+
+    #[test]
+    fn test_strict_decode_legacy() {
+        use lnpbp::strict_encoding::StrictEncode;
+
+        let asset = asset();
+        let mut data = vec![];
+        asset.id.strict_encode(&mut data).unwrap();
+        asset.ticker.strict_encode(&mut data).unwrap();
+        asset.name.strict_encode(&mut data).unwrap();
+        asset.description.strict_encode(&mut data).unwrap();
+        asset.supply.strict_encode(&mut data).unwrap();
+        asset.chain.strict_encode(&mut data).unwrap();
+        asset.fractional_bits.strict_encode(&mut data).unwrap();
+        asset.date.strict_encode(&mut data).unwrap();
+        asset.known_issues.strict_encode(&mut data).unwrap();
+        asset.known_inflation.strict_encode(&mut data).unwrap();
+        asset.unknown_inflation.strict_encode(&mut data).unwrap();
+        asset.known_allocations.strict_encode(&mut data).unwrap();
+
+        let legacy = Asset::strict_decode_legacy(&data[..]).unwrap();
+        assert_eq!(legacy.id, asset.id);
+        assert_eq!(legacy.supply, asset.supply);
+        assert_eq!(legacy.known_issues, asset.known_issues);
+        assert_eq!(legacy.known_allocations, asset.known_allocations);
+        assert!(legacy.known_rights.is_empty());
+        assert_eq!(legacy.schema_id, schema::schema().schema_id());
+        // Legacy layout is not accepted by the current decoder
+        assert!(Asset::strict_decode(&data[..]).is_err());
+    }
+}
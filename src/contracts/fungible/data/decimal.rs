@@ -0,0 +1,258 @@
+// RGB standard library
+// Written in 2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+use std::io;
+
+use lnpbp::rgb::prelude::*;
+use lnpbp::strict_encoding::{self, StrictDecode, StrictEncode};
+
+use super::AccountingValue;
+use crate::error::ParseError;
+
+/// Decimal amount of an asset in accounting units.
+///
+/// The amount is kept as an integer `mantissa` of `10^-precision` fractions,
+/// so unlike floating-point values it does not lose precision on the
+/// conversion into asset atomic values. Two amounts with different precision
+/// (like `1.5` and `1.50`) are distinct values, even if they denote the same
+/// number.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DecimalAmount {
+    mantissa: u128,
+    precision: u8,
+}
+
+impl DecimalAmount {
+    #[inline]
+    pub fn with(mantissa: u128, precision: u8) -> Self {
+        Self {
+            mantissa,
+            precision,
+        }
+    }
+
+    #[inline]
+    pub fn from_atomic_value(precision: u8, atomic_value: AtomicValue) -> Self {
+        Self::with(atomic_value as u128, precision)
+    }
+
+    /// Constructs decimal amount from a floating-point value using its
+    /// shortest decimal representation. Returns `None` for negative, infinite
+    /// and NaN values.
+    #[deprecated(
+        note = "floating-point amounts lose precision; parse decimal string \
+                with `DecimalAmount::from_str` instead"
+    )]
+    pub fn from_f32(value: AccountingValue) -> Option<Self> {
+        format!("{}", value).parse().ok()
+    }
+
+    #[inline]
+    pub fn mantissa(&self) -> u128 {
+        self.mantissa
+    }
+
+    #[inline]
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Converts the amount into atomic value of an asset with a given
+    /// `precision`, truncating fractional digits which can't be represented
+    /// with it. Returns `None` if the resulting value does not fit into
+    /// 64-bit integer.
+    pub fn checked_atomic_value(&self, precision: u8) -> Option<AtomicValue> {
+        let atoms = if precision >= self.precision {
+            10u128
+                .checked_pow((precision - self.precision) as u32)?
+                .checked_mul(self.mantissa)?
+        } else {
+            10u128
+                .checked_pow((self.precision - precision) as u32)
+                .map(|divisor| self.mantissa / divisor)
+                .unwrap_or(0)
+        };
+        if atoms > core::u64::MAX as u128 {
+            None
+        } else {
+            Some(atoms as AtomicValue)
+        }
+    }
+
+    /// Detects whether the amount has non-zero fractional digits which can't
+    /// be represented with the given `precision` and would be truncated by
+    /// [`DecimalAmount::checked_atomic_value`]
+    pub fn exceeds_precision(&self, precision: u8) -> bool {
+        if precision >= self.precision {
+            return false;
+        }
+        match 10u128.checked_pow((self.precision - precision) as u32) {
+            Some(divisor) => self.mantissa % divisor != 0,
+            None => self.mantissa != 0,
+        }
+    }
+}
+
+impl Display for DecimalAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.precision == 0 {
+            return write!(f, "{}", self.mantissa);
+        }
+        match 10u128.checked_pow(self.precision as u32) {
+            Some(divisor) => write!(
+                f,
+                "{}.{:0width$}",
+                self.mantissa / divisor,
+                self.mantissa % divisor,
+                width = self.precision as usize
+            ),
+            None => write!(
+                f,
+                "0.{:0>width$}",
+                self.mantissa,
+                width = self.precision as usize
+            ),
+        }
+    }
+}
+
+impl FromStr for DecimalAmount {
+    type Err = ParseError;
+
+    /// Parses decimal amount, ignoring `_`, `'` and `,` digit grouping
+    /// separators. Negative amounts and exponential notation are not
+    /// supported.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.replace(|c: char| c == '_' || c == '\'' || c == ',', "");
+        let mut parts = s.split('.');
+        let integer = parts.next().ok_or(ParseError)?;
+        let fraction = parts.next().unwrap_or("");
+        if parts.next().is_some()
+            || (integer.is_empty() && fraction.is_empty())
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+            || fraction.len() > core::u8::MAX as usize
+        {
+            return Err(ParseError);
+        }
+        let mantissa = integer
+            .chars()
+            .chain(fraction.chars())
+            .try_fold(0u128, |acc, c| {
+                acc.checked_mul(10)?.checked_add(c.to_digit(10)? as u128)
+            })
+            .ok_or(ParseError)?;
+        Ok(Self::with(mantissa, fraction.len() as u8))
+    }
+}
+
+impl StrictEncode for DecimalAmount {
+    type Error = strict_encoding::Error;
+
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Self::Error> {
+        self.to_string().strict_encode(e)
+    }
+}
+
+impl StrictDecode for DecimalAmount {
+    type Error = strict_encoding::Error;
+
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Self::Error> {
+        let s = String::strict_decode(d)?;
+        s.parse().map_err(|_| {
+            strict_encoding::Error::DataIntegrityError(format!(
+                "Wrong decimal amount encoding: {}",
+                s
+            ))
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DecimalAmount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DecimalAmount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        let amount = DecimalAmount::from_str("1234567.89").unwrap();
+        assert_eq!(amount, DecimalAmount::with(123456789, 2));
+        assert_eq!(amount.checked_atomic_value(2), Some(123456789));
+        assert_eq!(
+            DecimalAmount::from_atomic_value(2, 123456789).to_string(),
+            "1234567.89"
+        );
+    }
+
+    #[test]
+    fn test_decimal_rescale() {
+        let amount = DecimalAmount::from_str("12.5").unwrap();
+        assert_eq!(amount.checked_atomic_value(0), Some(12));
+        assert_eq!(amount.checked_atomic_value(8), Some(1_250_000_000));
+        assert_eq!(amount.checked_atomic_value(19), None);
+    }
+
+    #[test]
+    fn test_decimal_parse_errors() {
+        assert!(DecimalAmount::from_str("").is_err());
+        assert!(DecimalAmount::from_str(".").is_err());
+        assert!(DecimalAmount::from_str("-1").is_err());
+        assert!(DecimalAmount::from_str("1.2.3").is_err());
+        assert!(DecimalAmount::from_str("1e10").is_err());
+        assert_eq!(
+            DecimalAmount::from_str("1_000'000,000.5")
+                .unwrap()
+                .to_string(),
+            "1000000000.5"
+        );
+    }
+
+    #[test]
+    fn test_decimal_exceeds_precision() {
+        let amount = DecimalAmount::from_str("2.5").unwrap();
+        assert!(amount.exceeds_precision(0));
+        assert!(!amount.exceeds_precision(1));
+        assert!(!amount.exceeds_precision(8));
+        // Trailing zeros are not significant
+        assert!(!DecimalAmount::from_str("2.50")
+            .unwrap()
+            .exceeds_precision(1));
+        assert!(!DecimalAmount::from_str("3.000")
+            .unwrap()
+            .exceeds_precision(0));
+    }
+}
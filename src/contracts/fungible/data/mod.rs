@@ -12,13 +12,17 @@
 // If not, see <https://opensource.org/licenses/MIT>.
 
 mod asset;
+mod decimal;
 mod invoice;
 mod outcoins;
 pub mod schema;
 
 pub use asset::{
-    AccountingAmount, AccountingValue, Allocation, Asset, Issue, Supply,
+    AccountingAmount, AccountingValue, Allocation, Asset, ConcealedAllocation,
+    IssuanceReceipt, Issue, Right, RoundingMode, Supply, ASSET_JSON_VERSION,
+    PARALLEL_SUM_THRESHOLD,
 };
+pub use decimal::DecimalAmount;
 pub use invoice::{
     Error as InvoiceError, Invoice, Outpoint, OutpointDescriptor,
 };
@@ -23,7 +23,7 @@ use lnpbp::hex::FromHex;
 use lnpbp::rgb::SealDefinition;
 use lnpbp::strict_encoding::{self, StrictDecode, StrictEncode};
 
-use super::AccountingValue;
+use super::DecimalAmount;
 use crate::error::ParseError;
 
 #[derive(Clone, Debug, PartialEq, Display)]
@@ -34,8 +34,10 @@ use crate::error::ParseError;
 )]
 #[display(Debug)]
 pub struct Outcoins {
-    pub coins: AccountingValue,
+    pub coins: DecimalAmount,
     pub vout: u32,
+    /// Transaction containing the output; `None` defines the seal against
+    /// the output `vout` of the witness transaction, which is not known yet
     pub txid: Option<Txid>,
 }
 
@@ -47,15 +49,31 @@ pub struct Outcoins {
 )]
 #[display(Debug)]
 pub struct Outcoincealed {
-    pub coins: AccountingValue,
+    pub coins: DecimalAmount,
     pub seal_confidential: OutpointHash,
 }
 
 impl Outcoins {
+    /// Constructs output seal pointing to `vout` of the witness transaction
+    #[inline]
+    pub fn with_witness_vout(coins: DecimalAmount, vout: u32) -> Self {
+        Self {
+            coins,
+            vout,
+            txid: None,
+        }
+    }
+
     pub fn seal_definition(&self) -> SealDefinition {
         use lnpbp::bitcoin::secp256k1::rand::{self, RngCore};
         let mut rng = rand::thread_rng();
         let entropy = rng.next_u64(); // Not an amount blinding factor but outpoint blinding
+        self.seal_definition_with(entropy)
+    }
+
+    /// Constructs seal definition with a given outpoint blinding `entropy`
+    /// instead of a random one, which allows reproducible seal definitions
+    pub fn seal_definition_with(&self, entropy: u64) -> SealDefinition {
         match self.txid {
             Some(txid) => {
                 SealDefinition::TxOutpoint(bp::blind::OutpointReveal {
@@ -88,7 +106,7 @@ impl StrictDecode for Outcoins {
 
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Self::Error> {
         Ok(Self {
-            coins: f32::strict_decode(&mut d)?,
+            coins: DecimalAmount::strict_decode(&mut d)?,
             vout: u32::strict_decode(&mut d)?,
             txid: Option::<Txid>::strict_decode(&mut d)?,
         })
@@ -111,7 +129,7 @@ impl StrictDecode for Outcoincealed {
 
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Self::Error> {
         Ok(Self {
-            coins: f32::strict_decode(&mut d)?,
+            coins: DecimalAmount::strict_decode(&mut d)?,
             seal_confidential: OutpointHash::strict_decode(&mut d)?,
         })
     }
@@ -122,10 +140,10 @@ impl FromStr for Outcoins {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let re = Regex::new(
             r"(?x)
-                ^(?P<coins>[\d.,_']+) # float amount
+                ^(?P<coins>[\d.,_']+) # decimal amount
                 @
                 ((?P<txid>[a-f\d]{64}) # Txid
-                :)
+                :)? # Witness transaction if omitted
                 (?P<vout>\d+)$ # Vout
             ",
         )
@@ -155,7 +173,7 @@ impl FromStr for Outcoincealed {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let re = Regex::new(
             r"(?x)
-                ^(?P<coins>[\d.,_']+) # float amount
+                ^(?P<coins>[\d.,_']+) # decimal amount
                 @
                 ((?P<seal>[a-f\d]{64}))$ # Confidential seal: outpoint hash
             ",
@@ -174,3 +192,27 @@ impl FromStr for Outcoincealed {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_outcoins_parse() {
+        let txid =
+            "db2f3035e05795d72e2744dc0e88b2f72acbed97ee9a54c2c7f52d426ae05627";
+        let outcoins = Outcoins::from_str(&format!("1.5@{}:3", txid)).unwrap();
+        assert_eq!(outcoins.txid, Some(Txid::from_hex(txid).unwrap()));
+        assert_eq!(outcoins.vout, 3);
+
+        let witness = Outcoins::from_str("1.5@3").unwrap();
+        assert_eq!(
+            witness,
+            Outcoins::with_witness_vout("1.5".parse().unwrap(), 3)
+        );
+        match witness.seal_definition() {
+            SealDefinition::WitnessVout { vout, .. } => assert_eq!(vout, 3),
+            _ => panic!("Witness vout seal expected"),
+        }
+    }
+}
@@ -43,6 +43,8 @@ pub enum Error {
     NotAllFieldsPresent,
 
     WrongSchemaId,
+
+    IncompatibleSchema,
 }
 
 impl From<Error> for ServiceErrorDomain {
@@ -64,6 +66,42 @@ pub enum FieldType {
     BurnUtxo,
     HistoryProof,
     HistoryProofFormat,
+    ContactUri,
+    IssuerCommitment,
+    Attachment,
+    InflationNotBefore,
+}
+
+impl FieldType {
+    pub fn all() -> Vec<FieldType> {
+        vec![
+            FieldType::Ticker,
+            FieldType::Name,
+            FieldType::ContractText,
+            FieldType::Precision,
+            FieldType::IssuedSupply,
+            FieldType::BurnedSupply,
+            FieldType::Timestamp,
+            FieldType::BurnUtxo,
+            FieldType::HistoryProof,
+            FieldType::HistoryProofFormat,
+            FieldType::ContactUri,
+            FieldType::IssuerCommitment,
+            FieldType::Attachment,
+            FieldType::InflationNotBefore,
+        ]
+    }
+
+    /// Optional genesis fields which are not derived from the asset
+    /// nomination and supply, and may be provided as additional asset
+    /// metadata at the issue time
+    pub fn extra_nomination() -> Vec<FieldType> {
+        vec![
+            FieldType::ContactUri,
+            FieldType::IssuerCommitment,
+            FieldType::Attachment,
+        ]
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
@@ -77,6 +115,28 @@ pub enum OwnedRightsType {
     Renomination,
 }
 
+impl OwnedRightsType {
+    pub fn all() -> Vec<OwnedRightsType> {
+        vec![
+            OwnedRightsType::Inflation,
+            OwnedRightsType::Assets,
+            OwnedRightsType::Epoch,
+            OwnedRightsType::BurnReplace,
+            OwnedRightsType::Renomination,
+        ]
+    }
+}
+
+/// Declaration of a genesis metadata field by the schema, listed by
+/// [`field_catalog`]
+#[derive(Clone, PartialEq, Debug)]
+pub struct FieldKind {
+    /// How many times the field may be present in the genesis
+    pub occurences: Occurences,
+    /// Data type of the field values
+    pub format: DataFormat,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 #[display(Debug)]
 #[repr(u16)]
@@ -357,6 +417,129 @@ pub fn schema() -> Schema {
     }
 }
 
+/// Second version of the fungible assets schema, extending [`schema`] with
+/// optional genesis fields for the issuer contact URI, issuer identity
+/// commitment, binary attachment and the block height before which inflation
+/// rights can't be exercised, and with burn & replace rights assigned by the
+/// genesis.
+///
+/// The schema has its own id, so assets issued under [`schema`] keep matching
+/// it and do not need to be migrated; the processor uses the second version
+/// only when constructed with it by `Processor::with_schema`.
+pub fn schema_v2() -> Schema {
+    use Occurences::*;
+
+    let mut schema = self::schema();
+    schema.genesis.metadata.extend(type_map! {
+        FieldType::ContactUri => NoneOrOnce,
+        FieldType::IssuerCommitment => NoneOrOnce,
+        FieldType::Attachment => NoneOrOnce,
+        FieldType::InflationNotBefore => NoneOrOnce
+    });
+    schema
+        .genesis
+        .owned_rights
+        .insert(*OwnedRightsType::BurnReplace, NoneOrMore);
+    schema.field_types.extend(type_map! {
+        // URI for contacting the issuer, like `mailto:` or `https:` one
+        FieldType::ContactUri => DataFormat::String(1024),
+        // Commitment to the issuer identity, like a hash of the issuer
+        // public key or legal identity document
+        FieldType::IssuerCommitment => DataFormat::Bytes(32),
+        // Binary content committed to by the asset, like a document or its
+        // hash; up to 64kb
+        FieldType::Attachment => DataFormat::Bytes(core::u16::MAX),
+        // Block height before which inflation rights can't be exercised
+        FieldType::InflationNotBefore => DataFormat::Unsigned(Bits::Bit32, 0, core::u32::MAX as u128)
+    });
+    schema
+}
+
+/// Lists genesis metadata fields declared by the fungible assets `schema`
+/// version (like [`schema`] or [`schema_v2`]) with their occurrences and data
+/// types; field names are given by the [`FieldType`] display
+pub fn field_catalog(schema: &Schema) -> Vec<(FieldType, FieldKind)> {
+    FieldType::all()
+        .into_iter()
+        .filter_map(|field_type| {
+            Some((
+                field_type,
+                FieldKind {
+                    occurences: schema
+                        .genesis
+                        .metadata
+                        .get(&*field_type)?
+                        .clone(),
+                    format: schema.field_types.get(&*field_type)?.clone(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Lists owned rights types which may be assigned by the genesis of the
+/// fungible assets `schema` version
+pub fn rights_catalog(schema: &Schema) -> Vec<OwnedRightsType> {
+    OwnedRightsType::all()
+        .into_iter()
+        .filter(|right_type| {
+            schema.genesis.owned_rights.contains_key(&**right_type)
+        })
+        .collect()
+}
+
+/// Checks that `schema` is structurally compatible with the fungible assets
+/// schema: it must define all of its genesis metadata fields and owned rights
+/// with the same occurrences and data formats, and all of its state
+/// transition types. The schema may define additional fields, rights and
+/// transitions, and may narrow the range of unsigned integer fields (like
+/// the maximum asset precision).
+pub fn check_compatibility(schema: &Schema) -> Result<(), Error> {
+    let reference = self::schema();
+    let fields =
+        reference
+            .genesis
+            .metadata
+            .iter()
+            .all(|(field_type, occurences)| {
+                schema.genesis.metadata.get(field_type) == Some(occurences)
+                    && match (
+                        schema.field_types.get(field_type),
+                        reference.field_types.get(field_type),
+                    ) {
+                        (
+                            Some(DataFormat::Unsigned(bits, min, max)),
+                            Some(DataFormat::Unsigned(
+                                ref_bits,
+                                ref_min,
+                                ref_max,
+                            )),
+                        ) => {
+                            bits == ref_bits
+                                && min >= ref_min
+                                && max <= ref_max
+                                && min <= max
+                        }
+                        (format, ref_format) => format == ref_format,
+                    }
+            });
+    let rights = reference.genesis.owned_rights.iter().all(
+        |(right_type, occurences)| {
+            schema.genesis.owned_rights.get(right_type) == Some(occurences)
+                && schema.owned_right_types.get(right_type)
+                    == reference.owned_right_types.get(right_type)
+        },
+    );
+    let transitions = reference.transitions.keys().all(|transition_type| {
+        schema.transitions.contains_key(transition_type)
+    });
+    if fields && rights && transitions {
+        Ok(())
+    } else {
+        Err(Error::IncompatibleSchema)
+    }
+}
+
 impl Deref for FieldType {
     type Target = usize;
 
@@ -368,8 +551,12 @@ impl Deref for FieldType {
             FieldType::ContractText => &2,
             FieldType::Precision => &3,
             FieldType::Timestamp => &4,
+            FieldType::ContactUri => &5,
+            FieldType::IssuerCommitment => &6,
+            FieldType::Attachment => &7,
             // Inflation fields:
             FieldType::IssuedSupply => &FIELD_TYPE_ISSUED_SUPPLY,
+            FieldType::InflationNotBefore => &(FIELD_TYPE_ISSUED_SUPPLY + 1),
             // Proof-of-burn fields:
             FieldType::BurnedSupply => &FIELD_TYPE_BURN_SUPPLY,
             FieldType::BurnUtxo => &FIELD_TYPE_BURN_UTXO,
@@ -443,3 +630,67 @@ impl Deref for HistoryProofFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_catalog() {
+        let fields = field_catalog(&schema());
+        for field_type in &[
+            FieldType::Ticker,
+            FieldType::Name,
+            FieldType::Precision,
+            FieldType::Timestamp,
+            FieldType::IssuedSupply,
+            FieldType::ContractText,
+        ] {
+            assert!(fields.iter().any(|(known, _)| known == field_type));
+        }
+        // Proof-of-burn fields are not part of the genesis
+        assert!(!fields
+            .iter()
+            .any(|(known, _)| *known == FieldType::BurnedSupply));
+        let (_, ticker) = fields
+            .iter()
+            .find(|(known, _)| *known == FieldType::Ticker)
+            .unwrap();
+        assert_eq!(ticker.occurences, Occurences::Once);
+        assert_eq!(ticker.format, DataFormat::String(8));
+        assert_eq!(FieldType::Ticker.to_string(), "Ticker");
+        assert_eq!(fields.len(), schema().genesis.metadata.len());
+
+        let rights = rights_catalog(&schema());
+        assert!(rights.contains(&OwnedRightsType::Assets));
+        assert!(rights.contains(&OwnedRightsType::Inflation));
+        assert!(rights.contains(&OwnedRightsType::Renomination));
+        assert!(!rights.contains(&OwnedRightsType::BurnReplace));
+        assert_eq!(rights.len(), schema().genesis.owned_rights.len());
+
+        let fields = field_catalog(&schema_v2());
+        assert!(fields
+            .iter()
+            .any(|(known, _)| *known == FieldType::ContactUri));
+        assert_eq!(fields.len(), schema_v2().genesis.metadata.len());
+        // Prune seals defined on issue are assigned burn & replace rights
+        assert!(rights_catalog(&schema_v2())
+            .contains(&OwnedRightsType::BurnReplace));
+    }
+
+    #[test]
+    fn test_schema_versions() {
+        // Extending the first version must not change its id, which is
+        // committed to by all assets issued under it
+        let v1 = schema();
+        let v2 = schema_v2();
+        assert_ne!(v1.schema_id(), v2.schema_id());
+        assert!(!v1.genesis.metadata.contains_key(&*FieldType::ContactUri));
+        assert!(!v1
+            .genesis
+            .owned_rights
+            .contains_key(&*OwnedRightsType::BurnReplace));
+        assert_eq!(v1.transitions, v2.transitions);
+        assert_eq!(check_compatibility(&v2), Ok(()));
+    }
+}
@@ -19,13 +19,23 @@ mod runtime;
 pub(self) mod cache;
 
 pub use data::{
-    schema, AccountingAmount, Allocation, Asset, Error, Invoice, InvoiceError,
-    Issue, Outcoincealed, Outcoins, Outpoint, OutpointDescriptor, Supply,
+    schema, AccountingAmount, Allocation, Asset, ConcealedAllocation,
+    DecimalAmount, Error, Invoice, InvoiceError, IssuanceReceipt, Issue,
+    Outcoincealed, Outcoins, Outpoint, OutpointDescriptor, Right, RoundingMode,
+    Supply, ASSET_JSON_VERSION, PARALLEL_SUM_THRESHOLD,
 };
 
 pub use config::{Config, Opts};
 pub use runtime::{main_with_config, Runtime};
 
-pub use cache::{CacheError, FileCacheError, SqlCacheError};
-pub use processor::IssueStructure;
+pub use cache::{
+    AssetStorage, CacheError, FileCacheError, InMemoryAssetStorage,
+    MemoryCache, RgbStorage, SqlCacheError,
+};
 pub(self) use processor::Processor;
+pub use processor::{
+    BurnError, CoinSelection, CommitMethod, GenesisFormat, InflationError,
+    IssuancePreview, IssueBuilder, IssueObserver, IssueSpec, IssueStructure,
+    LocalCommitment, RenominationError, TransferError, TransferObserver,
+    TransferPlan, TransferRequest, TransferResult, ValidationError,
+};
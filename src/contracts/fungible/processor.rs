@@ -13,22 +13,410 @@
 
 use chrono::Utc;
 use core::convert::TryFrom;
+use core::fmt::Display;
 use serde::Deserialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::sync::{Arc, Mutex};
 
+use lnpbp::bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
 use lnpbp::bitcoin::OutPoint;
 use lnpbp::bp;
+use lnpbp::bp::blind::{OutpointHash, OutpointReveal};
+use lnpbp::client_side_validation::Conceal;
 use lnpbp::rgb::prelude::*;
+use lnpbp::rgb::schema::Schema;
 use lnpbp::secp256k1zkp;
+use lnpbp::strict_encoding::{self, StrictDecode, StrictEncode};
 
-use super::schema::{self, FieldType, OwnedRightsType, TransitionType};
-use super::{AccountingAmount, Allocation, Asset, Outcoincealed, Outcoins};
+use super::cache::{AssetStorage, RgbStorage};
+use super::schema::{
+    self, FieldType, HistoryProofFormat, OwnedRightsType, TransitionType,
+};
+use super::{
+    AccountingAmount, Allocation, Asset, DecimalAmount, Outcoincealed, Outcoins,
+};
 
+use crate::api::stash::ConsignRequest;
 use crate::error::{BootstrapError, ServiceErrorDomain};
+use crate::util::bech32m;
 use crate::util::SealSpec;
 use crate::{field, type_map};
 
-pub struct Processor {}
+/// Maximum number of steps performed by the branch-and-bound search in
+/// [`Processor::select_inputs_exact`]
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// Default maximum length of the asset description in bytes, matching the
+/// limit of the `ContractText` field in the schema
+pub const DEFAULT_DESCRIPTION_LIMIT: usize = core::u16::MAX as usize;
+
+/// Default maximum number of asset allocations created by a single issue or
+/// transfer, protecting against oversized transitions and consignments which
+/// downstream nodes may refuse
+pub const DEFAULT_MAX_OUTPUTS: usize = 1000;
+
+/// Maximum number of decimal fractional digits of asset amounts allowed by
+/// the RGB-20 schema
+pub const MAX_PRECISION: u8 = 18;
+
+/// Maximum length of the asset attachment in bytes allowed by the RGB-20
+/// schema
+pub const MAX_ATTACHMENT_LEN: usize = core::u16::MAX as usize;
+
+/// Callback notified by [`Processor`] of a newly issued asset and its genesis
+pub type IssueObserver = Box<dyn Fn(&Asset, &Genesis) + Send + Sync>;
+
+/// Callback notified by [`Processor`] of a newly constructed transfer state
+/// transition of the asset
+pub type TransferObserver = Box<dyn Fn(&Asset, &Transition) + Send + Sync>;
+
+pub struct Processor {
+    rgb_storage: Arc<Mutex<dyn RgbStorage>>,
+    asset_storage: Arc<Mutex<dyn AssetStorage>>,
+    /// Schema of the issued assets, which is the fungible assets schema or
+    /// a structurally compatible variant of it
+    schema: Schema,
+    /// Maximum length of the asset description in bytes accepted by `issue`
+    description_limit: usize,
+    /// Minimal amount in atomic units of allocations created by `issue`,
+    /// `inflate` and `transfer`, if the dust check is enabled
+    dust_limit: Option<AtomicValue>,
+    /// Maximum number of allocations created by `issue` and `transfer`
+    max_outputs: usize,
+    /// Network which assets are expected to be issued on; if set, assets of
+    /// other networks are not accepted by `transfer`, `inflate` and `burn`.
+    /// Transaction outpoints and blinded seals do not imply any network, so
+    /// this is the only network check for transition inputs and outputs.
+    chain: Option<bp::Chain>,
+    /// Networks which `issue` is allowed to create assets on; any network is
+    /// allowed if not set
+    issue_networks: Option<Vec<bp::Chain>>,
+    /// Callbacks registered with [`Processor::on_issue`]
+    issue_observers: Vec<IssueObserver>,
+    /// Callbacks registered with [`Processor::on_transfer`]
+    transfer_observers: Vec<TransferObserver>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum TransferError {
+    /// Insufficient funds: transfer requires {required} atomic units while
+    /// only {available} are available
+    InsufficientFunds {
+        required: AtomicValue,
+        available: AtomicValue,
+    },
+
+    /// No combination of known allocations sums up exactly to {target} atomic
+    /// units, so the transfer requires a change seal
+    ChangeRequired { target: AtomicValue },
+
+    /// Unknown input {_0}
+    UnknownInput(OutPoint),
+
+    /// Transfer input {_0} is known to the asset, but does not hold any
+    /// allocations
+    EmptyInput(OutPoint),
+
+    /// Transfer input {_0} is listed more than once
+    DuplicateInput(OutPoint),
+
+    /// Transfer input {_0} is already spent
+    AlreadySpent(OutPoint),
+
+    /// Confidential transfer input {_0} can't be resolved since its blinding
+    /// data are unknown
+    UnresolvableConfidentialInput(OutpointHash),
+
+    /// Transfer input {outpoint} holds allocation #{index} with concealed
+    /// amount, which was not revealed
+    MissingReveal { outpoint: OutPoint, index: u16 },
+
+    /// Revealed amount of allocation #{index} on {outpoint} does not match
+    /// its commitment
+    InvalidReveal { outpoint: OutPoint, index: u16 },
+
+    /// Input {_0} spent by a batch of transfers holds allocations of one of
+    /// the batch assets, which are not transferred
+    UnspentSharedSeal(OutPoint),
+
+    /// Output amount overflows 64-bit integer
+    OutputOverflow,
+
+    /// Transfer creates {count} allocations, exceeding the limit of {limit}
+    TooManyOutputs { count: usize, limit: usize },
+
+    /// Input amount of {available} atomic units is not equal to output amount
+    /// of {requested} atomic units, and the difference can't be assigned to
+    /// a change seal
+    AmountMismatch {
+        available: AtomicValue,
+        requested: AtomicValue,
+        /// Precision of the asset, for displaying the amounts in accounting
+        /// units
+        precision: u8,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum InflationError {
+    /// Outpoint {_0} does not hold a known inflation right for the asset
+    UnknownInflationRight(OutPoint),
+
+    /// Secondary issue of {requested} atomic units exceeds the amount of
+    /// {available} atomic units which still can be issued
+    MaxSupplyExceeded {
+        requested: AtomicValue,
+        available: AtomicValue,
+    },
+
+    /// Inflation rights of the asset can't be exercised before block
+    /// {not_before}; the current block height must be provided to
+    /// `Processor::inflate`
+    TimeLocked { not_before: u32 },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum BurnError {
+    /// Outpoint {_0} does not hold any known allocations of the asset
+    UnknownBurnInput(OutPoint),
+
+    /// There are no known burn & replace rights for the asset
+    NoBurnRight,
+
+    /// Burned amount must be non-zero
+    ZeroBurn,
+
+    /// Burned or replacement amount overflows 64-bit integer
+    AmountOverflow,
+
+    /// Burn of {requested} atomic units exceeds {available} atomic units
+    /// allocated to the burned outpoint
+    OverBurn {
+        requested: AtomicValue,
+        available: AtomicValue,
+    },
+
+    /// Burn leaves residual of {residual} atomic units, but no seal was
+    /// provided to assign it to
+    NoChangeSeal { residual: AtomicValue },
+
+    /// Replacement allocations of {replaced} atomic units do not match
+    /// {burned} atomic units of the burned allocations
+    ReplaceMismatch {
+        burned: AtomicValue,
+        replaced: AtomicValue,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum RenominationError {
+    /// Outpoint {_0} does not hold a known renomination right for the asset
+    UnknownRenominationRight(OutPoint),
+
+    /// Renomination must change either ticker or name of the asset
+    NothingToRenominate,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ValidationError {
+    /// Transition type {_0} is not defined by the asset schema
+    UnknownTransitionType(usize),
+
+    /// Metadata field of type {field_type} is not allowed for the transition
+    /// type {transition_type}
+    UnexpectedField {
+        transition_type: usize,
+        field_type: usize,
+    },
+
+    /// Transition of type {transition_type} can't assign owned rights of
+    /// type {right_type}
+    UnexpectedOwnedRights {
+        transition_type: usize,
+        right_type: usize,
+    },
+
+    /// Transition of type {transition_type} can't close owned rights of type
+    /// {right_type}
+    UnexpectedClosedRights {
+        transition_type: usize,
+        right_type: usize,
+    },
+
+    /// Transition spends assignment #{index} of node {node_id}, which is not
+    /// a known revealed allocation of the asset
+    UnknownParentAllocation { node_id: NodeId, index: u16 },
+
+    /// Transition assigns an amount which is not revealed, so its balance
+    /// can't be verified
+    ConcealedAmount,
+
+    /// Sum of inputs ({inputs} atomic units) is not equal to the sum of
+    /// outputs ({outputs} atomic units)
+    Unbalanced {
+        inputs: AtomicValue,
+        outputs: AtomicValue,
+    },
+}
+
+/// Picks `candidates` in the given order until they cover the `target` amount
+fn accumulate_inputs(
+    candidates: &[(OutPoint, AtomicValue)],
+    target: AtomicValue,
+) -> Result<Vec<OutPoint>, ServiceErrorDomain> {
+    let mut selected = vec![];
+    let mut covered = 0u64;
+    for (outpoint, amount) in candidates {
+        if covered >= target {
+            break;
+        }
+        covered = covered.saturating_add(*amount);
+        selected.push(*outpoint);
+    }
+    if covered < target {
+        Err(TransferError::InsufficientFunds {
+            required: target,
+            available: covered,
+        })?
+    }
+    Ok(selected)
+}
+
+/// Generates random amount blinding factor for Pedersen commitments; constant
+/// blinding factors would make the committed amounts trivially recoverable
+fn random_blinding() -> secp256k1zkp::key::SecretKey {
+    use lnpbp::bitcoin::secp256k1::rand::{self, RngCore};
+    let secp = secp256k1zkp::Secp256k1::new();
+    let mut rng = rand::thread_rng();
+    loop {
+        let mut entropy = [0u8; 32];
+        rng.fill_bytes(&mut entropy);
+        // Random 32 bytes may (with negligible probability) be out of the
+        // curve order, so we just retry in this case
+        if let Ok(key) =
+            secp256k1zkp::key::SecretKey::from_slice(&secp, &entropy)
+        {
+            break key;
+        }
+    }
+}
+
+/// Source of the genesis seal and amount blinding factors, which are random
+/// unless a seed is given; seeded factors make the genesis reproducible
+struct IssueEntropy {
+    seed: Option<[u8; 32]>,
+    counter: u32,
+}
+
+impl IssueEntropy {
+    #[inline]
+    fn with(seed: Option<[u8; 32]>) -> Self {
+        Self { seed, counter: 0 }
+    }
+
+    /// Derives next portion of entropy from the `seed`
+    fn derive(&mut self, seed: &[u8; 32]) -> [u8; 32] {
+        use lnpbp::hashes::{sha256, Hash, HashEngine};
+        let mut engine = sha256::Hash::engine();
+        engine.input(seed);
+        engine.input(&self.counter.to_le_bytes());
+        self.counter += 1;
+        sha256::Hash::from_engine(engine).into_inner()
+    }
+
+    /// Returns outpoint blinding for the next seal definition
+    fn seal_blinding(&mut self) -> u64 {
+        use lnpbp::bitcoin::secp256k1::rand::{self, RngCore};
+        match self.seed {
+            None => rand::thread_rng().next_u64(),
+            Some(seed) => {
+                let mut blinding = [0u8; 8];
+                blinding.copy_from_slice(&self.derive(&seed)[..8]);
+                u64::from_le_bytes(blinding)
+            }
+        }
+    }
+
+    /// Returns next amount blinding factor
+    fn amount_blinding(&mut self) -> secp256k1zkp::key::SecretKey {
+        let seed = match self.seed {
+            None => return random_blinding(),
+            Some(seed) => seed,
+        };
+        let secp = secp256k1zkp::Secp256k1::new();
+        loop {
+            // Derived 32 bytes may (with negligible probability) be out of
+            // the curve order, so we just derive the next ones in this case
+            if let Ok(key) = secp256k1zkp::key::SecretKey::from_slice(
+                &secp,
+                &self.derive(&seed),
+            ) {
+                break key;
+            }
+        }
+    }
+
+    /// Works like [`Assignments::zero_balanced`] for a single `input`, but
+    /// takes amount blinding factors of the outputs from the seed, if any
+    fn zero_balanced(
+        &mut self,
+        input: value::Revealed,
+        allocations_ours: Vec<(seal::Revealed, AtomicValue)>,
+        allocations_theirs: Vec<(OutpointHash, AtomicValue)>,
+    ) -> Assignments {
+        if self.seed.is_none() {
+            return Assignments::zero_balanced(
+                vec![input],
+                allocations_ours,
+                allocations_theirs,
+            );
+        }
+        let count = allocations_ours.len() + allocations_theirs.len();
+        let mut blindings = (1..count)
+            .map(|_| self.amount_blinding())
+            .collect::<Vec<_>>();
+        if count > 0 {
+            // The last output takes blinding factor balancing the input
+            let secp = secp256k1zkp::Secp256k1::with_caps(
+                secp256k1zkp::ContextFlag::Commit,
+            );
+            blindings.push(
+                secp.blind_sum(vec![input.blinding], blindings.clone())
+                    .expect(
+                        "Balancing blinding factor is zero with negligible \
+                         probability",
+                    ),
+            );
+        }
+        let mut blindings = blindings.into_iter();
+        let mut next_value = |value| value::Revealed {
+            value,
+            blinding: blindings
+                .next()
+                .expect("Blinding factors are generated for each output"),
+        };
+        let mut states = allocations_ours
+            .into_iter()
+            .map(|(seal_definition, value)| OwnedState::Revealed {
+                seal_definition,
+                assigned_state: next_value(value),
+            })
+            .collect::<Vec<_>>();
+        states.extend(allocations_theirs.into_iter().map(
+            |(seal_definition, value)| OwnedState::ConfidentialSeal {
+                seal_definition,
+                assigned_state: next_value(value),
+            },
+        ));
+        Assignments::DiscreteFiniteField(states)
+    }
+}
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 #[cfg_attr(
@@ -39,214 +427,6647 @@ pub struct Processor {}
 pub enum IssueStructure {
     SingleIssue,
     MultipleIssues {
-        max_supply: f32,
+        /// Maximum total supply of the asset expressed in atomic values
+        /// (i.e. with precision already applied)
+        max_supply: AtomicValue,
         reissue_control: SealSpec,
+        /// Block height before which the inflation rights can't be exercised
+        inflation_not_before: Option<u32>,
     },
 }
 
-impl Processor {
-    pub fn new() -> Result<Self, BootstrapError> {
-        debug!("Instantiating RGB asset manager ...");
-
-        let me = Self {};
-        /*
-        let storage = rgb_storage.clone();
-        let me = Self {
-            rgb_storage,
-            asset_storage,
-        };
-         */
-        let _schema = schema::schema();
-        //if !me.rgb_storage.lock()?.has_schema(schema.schema_id())? {
-        info!("RGB fungible assets schema file not found, creating one");
-        //storage.lock()?.add_schema(&schema)?;
-        //}
+// Encoded as a single byte tag of the variant, followed by `max_supply` and
+// `reissue_control` for `MultipleIssues`. Time-locked `MultipleIssues` use a
+// separate tag followed by the lock height, so encoding of the issue
+// structures without time lock is not changed.
+impl StrictEncode for IssueStructure {
+    type Error = strict_encoding::Error;
 
-        Ok(me)
+    fn strict_encode<E: io::Write>(
+        &self,
+        mut e: E,
+    ) -> Result<usize, Self::Error> {
+        Ok(match self {
+            IssueStructure::SingleIssue => 0u8.strict_encode(e)?,
+            IssueStructure::MultipleIssues {
+                max_supply,
+                reissue_control,
+                inflation_not_before: None,
+            } => strict_encode_list!(e; 1u8, max_supply, reissue_control),
+            IssueStructure::MultipleIssues {
+                max_supply,
+                reissue_control,
+                inflation_not_before: Some(height),
+            } => {
+                strict_encode_list!(e; 2u8, max_supply, reissue_control, height)
+            }
+        })
     }
+}
 
-    pub fn issue(
-        &mut self,
-        network: bp::Chain,
-        ticker: String,
-        name: String,
-        description: Option<String>,
-        issue_structure: IssueStructure,
-        allocations: Vec<Outcoins>,
-        precision: u8,
-        prune_seals: Vec<SealSpec>,
-    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
-        let now = Utc::now().timestamp();
-        let mut metadata = type_map! {
-            FieldType::Ticker => field!(String, ticker),
-            FieldType::Name => field!(String, name),
-            FieldType::Precision => field!(U8, precision),
-            FieldType::Timestamp => field!(I64, now)
-        };
-        if let Some(description) = description {
-            metadata
-                .insert(*FieldType::ContractText, field!(String, description));
+impl StrictDecode for IssueStructure {
+    type Error = strict_encoding::Error;
+
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Self::Error> {
+        match u8::strict_decode(&mut d)? {
+            0 => Ok(IssueStructure::SingleIssue),
+            1 => Ok(IssueStructure::MultipleIssues {
+                max_supply: AtomicValue::strict_decode(&mut d)?,
+                reissue_control: SealSpec::strict_decode(&mut d)?,
+                inflation_not_before: None,
+            }),
+            2 => Ok(IssueStructure::MultipleIssues {
+                max_supply: AtomicValue::strict_decode(&mut d)?,
+                reissue_control: SealSpec::strict_decode(&mut d)?,
+                inflation_not_before: Some(u32::strict_decode(&mut d)?),
+            }),
+            tag => Err(strict_encoding::Error::DataIntegrityError(format!(
+                "Unknown issue structure tag {}",
+                tag
+            ))),
         }
+    }
+}
 
-        let mut issued_supply = 0u64;
-        let allocations = allocations
-            .into_iter()
-            .map(|outcoins| {
-                let amount =
-                    AccountingAmount::transmutate(precision, outcoins.coins);
-                issued_supply += amount;
-                (outcoins.seal_definition(), amount)
-            })
-            .collect();
-        let mut owned_rights = BTreeMap::new();
-        owned_rights.insert(
-            *OwnedRightsType::Assets,
-            Assignments::zero_balanced(
-                vec![value::Revealed {
-                    value: issued_supply,
-                    blinding: secp256k1zkp::key::ONE_KEY,
-                }],
-                allocations,
-                vec![],
-            ),
-        );
-        metadata.insert(*FieldType::IssuedSupply, field!(U64, issued_supply));
+/// Strategy for the automatic selection of transfer inputs among the
+/// outpoints with known asset allocations
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CoinSelection {
+    /// Spends the largest allocations first, minimizing number of inputs
+    LargestFirst,
+    /// Spends the smallest allocations first, consolidating them
+    SmallestFirst,
+    /// Searches for the inputs matching the target amount exactly, so no
+    /// change is required, falling back to [`CoinSelection::LargestFirst`]
+    BranchAndBound,
+    /// Uses the given inputs, checking that they cover the target amount
+    Manual(Vec<OutPoint>),
+}
 
-        if let IssueStructure::MultipleIssues {
-            max_supply,
-            reissue_control,
-        } = issue_structure
-        {
-            let total_supply =
-                AccountingAmount::transmutate(precision, max_supply);
-            if total_supply < issued_supply {
-                Err(ServiceErrorDomain::Schema(format!(
-                    "Total supply ({}) should be greater than the issued supply ({})",
-                    total_supply, issued_supply
-                )))?;
-            }
-            owned_rights.insert(
-                *OwnedRightsType::Inflation,
-                Assignments::Declarative(vec![OwnedState::Revealed {
-                    seal_definition: reissue_control.seal_definition(),
-                    assigned_state: data::Void,
-                }]),
-            );
-        }
+/// Transfer state transition together with the owned rights it spends,
+/// produced by [`Processor::transfer`]
+#[derive(Clone, PartialEq, Debug)]
+pub struct TransferResult {
+    pub transition: Transition,
+    /// Outpoints spent by the transfer, including the ones resolved from
+    /// confidential inputs or selected automatically, which must be spent by
+    /// the anchoring transaction
+    pub inputs: Vec<OutPoint>,
+    /// Node id, owned rights type and assignment index of each spent input
+    /// allocation, in the order of the transfer inputs
+    pub spent: Vec<(NodeId, OwnedRightsType, u16)>,
+    /// Blinding factors of the amount commitments generated for the
+    /// allocations on blinded seals, which the recipients require to spend
+    /// them. Allocations on revealed seals and the issued ones keep their
+    /// blinding factors within [`Allocation`] values of the asset.
+    pub blindings: BTreeMap<OutpointHash, secp256k1zkp::key::SecretKey>,
+    /// Amounts and blinding factors of the outputs with concealed amounts
+    /// (see [`TransferRequest::conceal_amounts`]), indexed by concealed
+    /// seal; empty for transfers with revealed amounts
+    pub reveals: BTreeMap<OutpointHash, value::Revealed>,
+}
 
-        if prune_seals.len() > 0 {
-            owned_rights.insert(
-                *OwnedRightsType::BurnReplace,
-                Assignments::Declarative(
-                    prune_seals
-                        .into_iter()
-                        .map(|seal_spec| OwnedState::Revealed {
-                            seal_definition: seal_spec.seal_definition(),
-                            assigned_state: data::Void,
-                        })
-                        .collect(),
-                ),
-            );
-        }
+/// Accumulates parameters of a fungible asset transfer performed with
+/// [`Processor::transfer`], or of a single asset transfer within a batch
+/// sent by [`Processor::transfer_multi`]
+#[derive(PartialEq, Debug)]
+pub struct TransferRequest<'a> {
+    asset: &'a mut Asset,
+    inputs: Vec<OutPoint>,
+    confidential_inputs: Vec<OutpointHash>,
+    coin_selection: Option<CoinSelection>,
+    reveals: BTreeMap<(NodeId, u16), value::Revealed>,
+    already_spent: BTreeSet<OutPoint>,
+    ours: Vec<Outcoins>,
+    theirs: Vec<Outcoincealed>,
+    fee: Option<(SealSpec, DecimalAmount)>,
+    change: Option<ChangeSeal>,
+    conceal_amounts: bool,
+}
 
-        let genesis = Genesis::with(
-            schema::schema().schema_id(),
-            network,
-            metadata.into(),
-            owned_rights,
-            bset![],
-            vec![],
-        );
+/// Checked transfer which is not yet turned into a state transition,
+/// produced by [`Processor::validate_transfer`] for previewing the transfer
+#[derive(Clone, Getters, PartialEq, Debug)]
+pub struct TransferPlan {
+    /// Input allocations with revealed amounts
+    inputs: Vec<Allocation>,
+    /// Allocations on our seals, not including change
+    ours: Vec<(seal::Revealed, AtomicValue)>,
+    /// Allocations on blinded seals of other parties
+    theirs: Vec<(OutpointHash, AtomicValue)>,
+    /// Fee allocation on the designated fee seal
+    fee: Option<(seal::Revealed, AtomicValue)>,
+    /// Surplus of inputs over outputs assigned to the change seal
+    change: Option<(seal::Revealed, AtomicValue)>,
+    /// Whether the change seal is concealed in the transition, like the
+    /// seals of other parties
+    blinded_change: bool,
+    /// Whether amounts of all the outputs are kept only as Pedersen
+    /// commitments
+    conceal_amounts: bool,
+}
 
-        let asset = Asset::try_from(genesis.clone())?;
-        //self.asset_storage.lock()?.add_asset(asset.clone())?;
+/// Supply of the asset which would be issued, computed by
+/// [`Processor::validate_issue`] without constructing the genesis
+#[derive(Clone, Copy, Getters, PartialEq, Eq, Debug, Display)]
+#[display(Debug)]
+pub struct IssuancePreview {
+    /// Supply of the primary issue in atomic units
+    issued_supply: AtomicValue,
+    /// Maximum total supply in atomic units, equal to the issued supply for
+    /// non-inflatable assets
+    total_supply: AtomicValue,
+}
 
-        Ok((asset, genesis))
-    }
+/// Human-readable part of the Bech32m-encoded genesis
+pub const GENESIS_BECH32M_HRP: &str = "genesis";
 
-    /// Function creates a fungible asset-specific state transition (i.e. RGB-20
-    /// schema-based) given an asset information, inputs and desired outputs
-    pub fn transfer(
-        &mut self,
-        asset: &mut Asset,
+/// Serialization format of the asset genesis produced by
+/// [`Processor::export_genesis`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display)]
+#[display(Debug)]
+pub enum GenesisFormat {
+    /// Binary strict encoding
+    StrictEncode,
+    /// Bech32m string (BIP-350) of the strict encoding with
+    /// [`GENESIS_BECH32M_HRP`] human-readable part
+    Bech32m,
+    /// Base64 string of the strict encoding
+    #[cfg(feature = "base64")]
+    Base64,
+}
+
+/// Commitment method a [`LocalCommitment`] is computed for, see
+/// [`Processor::local_commitment`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display)]
+#[display(Debug)]
+pub enum CommitMethod {
+    /// Transition is going to be committed with an `OP_RETURN` output
+    OpReturn,
+    /// Transition is going to be committed with a taproot script leaf
+    Taproot,
+}
+
+/// Processor-local commitment to a single state transition, see
+/// [`Processor::local_commitment`]. It is not an LNPBP-4 multi-protocol
+/// commitment and must not be put into a transaction.
+#[derive(Clone, Copy, Getters, PartialEq, Eq, Hash, Debug, Display)]
+#[display(Debug)]
+pub struct LocalCommitment {
+    /// Method of embedding the commitment
+    method: CommitMethod,
+    /// Id of the committed transition
+    node_id: NodeId,
+    /// Tagged hash of the transition id specific to the commit method
+    digest: [u8; 32],
+}
+
+/// Seal receiving the change of a transfer
+#[derive(Clone, PartialEq, Debug)]
+enum ChangeSeal {
+    Revealed(SealSpec),
+    /// Seal on our outpoint which is concealed in the transition
+    Blinded(OutpointReveal),
+}
+
+impl<'a> TransferRequest<'a> {
+    pub fn new(
+        asset: &'a mut Asset,
         inputs: Vec<OutPoint>,
         ours: Vec<Outcoins>,
         theirs: Vec<Outcoincealed>,
-    ) -> Result<Transition, ServiceErrorDomain> {
-        // Collecting all input allocations
-        let mut input_allocations = Vec::<Allocation>::new();
-        for seal in &inputs {
-            let found = asset
-                .allocations(seal)
-                .ok_or(format!("Unknown input {}", seal))?
-                .clone();
-            if found.len() == 0 {
-                Err(format!("Unknown input {}", seal))?
-            }
-            input_allocations.extend(found);
+    ) -> Self {
+        Self {
+            asset,
+            inputs,
+            confidential_inputs: vec![],
+            coin_selection: None,
+            reveals: bmap! {},
+            already_spent: bset![],
+            ours,
+            theirs,
+            fee: None,
+            change: None,
+            conceal_amounts: false,
         }
-        // Computing sum of inputs
-        let total_inputs = input_allocations
+    }
+
+    /// Adds input referenced by the blinded outpoint hash of the allocations
+    /// received to a confidential seal
+    pub fn confidential_input(mut self, seal: OutpointHash) -> Self {
+        self.confidential_inputs.push(seal);
+        self
+    }
+
+    /// Selects the inputs among the outpoints with known asset allocations
+    /// with the given strategy (see [`Processor::select_coins`]) instead of
+    /// the explicitly given ones. Without change seal the selected inputs
+    /// must match the outputs exactly.
+    pub fn coin_selection(mut self, selection: CoinSelection) -> Self {
+        self.coin_selection = Some(selection);
+        self
+    }
+
+    /// Provides amount and blinding factor `value` of the input allocation
+    /// with concealed amount, identified by its node id and assignment
+    /// `index`, so it can be spent
+    pub fn reveal(
+        mut self,
+        node_id: NodeId,
+        index: u16,
+        value: value::Revealed,
+    ) -> Self {
+        self.reveals.insert((node_id, index), value);
+        self
+    }
+
+    /// Marks `outpoint` as already consumed by some other transition, so
+    /// allocations on it from a stale asset state are rejected before
+    /// building the transition
+    pub fn already_spent(mut self, outpoint: OutPoint) -> Self {
+        self.already_spent.insert(outpoint);
+        self
+    }
+
+    /// Routes `amount` of the asset to a designated fee seal. The fee is
+    /// tracked separately from `ours` and `theirs` outputs and is counted
+    /// into the total outputs, so the inputs must cover outputs plus fee.
+    pub fn fee(mut self, seal: SealSpec, amount: DecimalAmount) -> Self {
+        self.fee = Some((seal, amount));
+        self
+    }
+
+    /// Sets seal receiving the surplus of inputs over outputs
+    pub fn change_seal(mut self, seal: SealSpec) -> Self {
+        self.change = Some(ChangeSeal::Revealed(seal));
+        self
+    }
+
+    /// Assigns the change to our outpoint concealed in the transition with
+    /// `reveal` blinding data. The blinding data are kept by the asset, so
+    /// once the change allocation is added with [`Asset::apply_transition`]
+    /// it can be spent with [`TransferRequest::confidential_input`]. Amount
+    /// blinding factor of the change is returned in
+    /// [`TransferResult::blindings`].
+    pub fn blinded_change(mut self, reveal: OutpointReveal) -> Self {
+        self.change = Some(ChangeSeal::Blinded(reveal));
+        self
+    }
+
+    /// Keeps amounts of all the outputs, including change, only as Pedersen
+    /// commitments with range proofs. The amounts and their blinding factors
+    /// are returned in [`TransferResult::reveals`], so they can be revealed
+    /// to the owners of the outputs.
+    pub fn conceal_amounts(mut self) -> Self {
+        self.conceal_amounts = true;
+        self
+    }
+}
+
+impl TransferPlan {
+    /// Sum of the input allocations in atomic units
+    pub fn total_inputs(&self) -> AtomicValue {
+        self.inputs
             .iter()
-            .fold(0u64, |acc, alloc| acc + alloc.value().value);
+            .fold(0u64, |acc, alloc| acc + alloc.value().value)
+    }
 
-        let metadata = type_map! {};
-        let mut total_outputs = 0;
-        let allocations_ours = ours
-            .into_iter()
-            .map(|outcoins| {
-                let amount = AccountingAmount::transmutate(
-                    *asset.fractional_bits(),
-                    outcoins.coins,
-                );
-                total_outputs += amount;
-                (outcoins.seal_definition(), amount)
-            })
-            .collect();
-        let allocations_theirs = theirs
-            .into_iter()
-            .map(|outcoincealed| {
-                let amount = AccountingAmount::transmutate(
-                    *asset.fractional_bits(),
-                    outcoincealed.coins,
-                );
-                total_outputs += amount;
-                (outcoincealed.seal_confidential, amount)
-            })
-            .collect();
+    /// Sum of the outputs in atomic units, including fee but not including
+    /// change
+    pub fn total_outputs(&self) -> AtomicValue {
+        let ours = self.ours.iter().map(|(_, amount)| amount);
+        let theirs = self.theirs.iter().map(|(_, amount)| amount);
+        let fee = self.fee.iter().map(|(_, amount)| amount);
+        ours.chain(theirs).chain(fee).sum()
+    }
 
-        if total_inputs != total_outputs {
-            Err("Input amount is not equal to output amount".to_string())?
-        }
+    /// Outpoints consumed by the transfer, which must be spent by the
+    /// anchoring transaction
+    pub fn consumed(&self) -> BTreeSet<OutPoint> {
+        self.inputs.iter().map(|alloc| *alloc.outpoint()).collect()
+    }
 
-        let input_amounts = input_allocations
+    /// Concealed definitions of the seals receiving the outputs, not
+    /// including change, in the order of the outputs (with the fee seal
+    /// going last)
+    pub fn produced(&self) -> Vec<seal::Confidential> {
+        self.ours
             .iter()
-            .map(|alloc| alloc.value().clone())
-            .collect();
-        let assignments = type_map! {
-            OwnedRightsType::Assets =>
-            Assignments::zero_balanced(input_amounts, allocations_ours, allocations_theirs)
-        };
+            .map(|(seal_definition, _)| seal_definition.conceal())
+            .chain(
+                self.theirs
+                    .iter()
+                    .map(|(seal_confidential, _)| *seal_confidential),
+            )
+            .chain(
+                self.fee
+                    .iter()
+                    .map(|(seal_definition, _)| seal_definition.conceal()),
+            )
+            .collect()
+    }
 
-        let mut parent = ParentOwnedRights::new();
-        for alloc in input_allocations {
-            parent
-                .entry(*alloc.node_id())
-                .or_insert(bmap! {})
-                .entry(*OwnedRightsType::Assets)
-                .or_insert(vec![])
-                .push(*alloc.index());
+    /// Seal receiving the change, if any
+    #[inline]
+    pub fn change_seal(&self) -> Option<&seal::Revealed> {
+        self.change
+            .as_ref()
+            .map(|(seal_definition, _)| seal_definition)
+    }
+}
+
+/// Validated arguments for [`Processor::issue`], produced by
+/// [`IssueBuilder::spec`]
+#[derive(Clone, PartialEq, Debug)]
+pub struct IssueSpec {
+    pub network: bp::Chain,
+    pub ticker: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub issue_structure: IssueStructure,
+    pub allocations: Vec<Outcoins>,
+    pub precision: u8,
+    pub prune_seals: Vec<SealSpec>,
+    pub timestamp: Option<i64>,
+    pub extra_fields: BTreeMap<FieldType, data::Revealed>,
+    /// Blinding factor of the issued supply commitment; random if not given
+    pub blinding: Option<secp256k1zkp::key::SecretKey>,
+    /// Seed for the seal and amount blinding factors of the genesis, making
+    /// it reproducible; the factors are random if not given
+    pub blinding_seed: Option<[u8; 32]>,
+    /// Seal receiving the right to rename the asset with
+    /// [`Processor::renominate`]
+    pub renomination_seal: Option<SealSpec>,
+    /// Additional seals receiving inflation rights with the given allowance
+    /// in atomic units; the rest of the inflation allowance goes to the
+    /// reissue control seal of [`IssueStructure::MultipleIssues`]
+    pub reissue_shares: Vec<(SealSpec, AtomicValue)>,
+    /// Allocations of the issued supply on blinded seals, which are not
+    /// revealed in the genesis
+    pub confidential_allocations: Vec<Outcoincealed>,
+}
+
+/// Accumulates parameters of a new asset issue, deriving the
+/// [`IssueStructure`] from the presence of the inflation parameters
+#[derive(Clone, PartialEq, Debug)]
+pub struct IssueBuilder {
+    network: bp::Chain,
+    ticker: String,
+    name: String,
+    description: Option<String>,
+    allocations: Vec<Outcoins>,
+    precision: u8,
+    max_supply: Option<AtomicValue>,
+    reissue_control: Option<SealSpec>,
+    prune_seals: Vec<SealSpec>,
+    timestamp: Option<i64>,
+    extra_fields: BTreeMap<FieldType, data::Revealed>,
+    blinding: Option<[u8; 32]>,
+    blinding_seed: Option<[u8; 32]>,
+    renomination_seal: Option<SealSpec>,
+    reissue_shares: Vec<(SealSpec, AtomicValue)>,
+    confidential_allocations: Vec<Outcoincealed>,
+    inflation_not_before: Option<u32>,
+    store: bool,
+}
+
+impl IssueBuilder {
+    pub fn new(network: bp::Chain, ticker: &str, name: &str) -> Self {
+        Self {
+            network,
+            ticker: ticker.to_string(),
+            name: name.to_string(),
+            description: None,
+            allocations: vec![],
+            precision: 0,
+            max_supply: None,
+            reissue_control: None,
+            prune_seals: vec![],
+            timestamp: None,
+            extra_fields: bmap! {},
+            blinding: None,
+            blinding_seed: None,
+            renomination_seal: None,
+            reissue_shares: vec![],
+            confidential_allocations: vec![],
+            inflation_not_before: None,
+            store: true,
         }
+    }
 
-        let transition = Transition::with(
-            *TransitionType::Transfer,
+    /// Starts issue parameters without asset ticker and name, which must be
+    /// provided with [`IssueBuilder::ticker`] and [`IssueBuilder::name`]
+    /// before the asset is issued
+    #[inline]
+    pub fn with_network(network: bp::Chain) -> Self {
+        Self::new(network, "", "")
+    }
+
+    pub fn network(mut self, network: bp::Chain) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn ticker(mut self, ticker: &str) -> Self {
+        self.ticker = ticker.to_string();
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn precision(mut self, precision: u8) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn allocation(mut self, allocation: Outcoins) -> Self {
+        self.allocations.push(allocation);
+        self
+    }
+
+    /// Adds allocation of the issued supply to a blinded seal, so the
+    /// receiving outpoint is not revealed in the genesis
+    pub fn confidential_allocation(
+        mut self,
+        allocation: Outcoincealed,
+    ) -> Self {
+        self.confidential_allocations.push(allocation);
+        self
+    }
+
+    /// Sets maximum total supply of the asset in atomic units, making it
+    /// inflatable; requires [`IssueBuilder::reissue_control`] seal
+    pub fn max_supply(mut self, max_supply: AtomicValue) -> Self {
+        self.max_supply = Some(max_supply);
+        self
+    }
+
+    /// Sets seal controlling secondary issues; requires
+    /// [`IssueBuilder::max_supply`]
+    pub fn reissue_control(mut self, seal: SealSpec) -> Self {
+        self.reissue_control = Some(seal);
+        self
+    }
+
+    /// Makes the asset inflatable up to `max_supply` atomic units, with the
+    /// inflation right assigned to `reissue_control` seal
+    #[inline]
+    pub fn inflation(
+        self,
+        max_supply: AtomicValue,
+        reissue_control: SealSpec,
+    ) -> Self {
+        self.max_supply(max_supply).reissue_control(reissue_control)
+    }
+
+    /// Sets inflation parameters of the asset from `issue_structure`,
+    /// replacing the ones given before
+    pub fn issue_structure(mut self, issue_structure: IssueStructure) -> Self {
+        match issue_structure {
+            IssueStructure::SingleIssue => {
+                self.max_supply = None;
+                self.reissue_control = None;
+                self.inflation_not_before = None;
+            }
+            IssueStructure::MultipleIssues {
+                max_supply,
+                reissue_control,
+                inflation_not_before,
+            } => {
+                self.max_supply = Some(max_supply);
+                self.reissue_control = Some(reissue_control);
+                self.inflation_not_before = inflation_not_before;
+            }
+        }
+        self
+    }
+
+    /// Adds seal receiving inflation right with `allowance` part of the
+    /// inflation cap in atomic units, so secondary issues may be controlled
+    /// by several parties; requires [`IssueBuilder::max_supply`]
+    pub fn reissue_share(
+        mut self,
+        seal: SealSpec,
+        allowance: AtomicValue,
+    ) -> Self {
+        self.reissue_shares.push((seal, allowance));
+        self
+    }
+
+    /// Sets block height before which the inflation rights can't be
+    /// exercised
+    pub fn inflation_not_before(mut self, height: u32) -> Self {
+        self.inflation_not_before = Some(height);
+        self
+    }
+
+    /// Adds seal receiving burn & replace right
+    pub fn prune_seal(mut self, seal: SealSpec) -> Self {
+        self.prune_seals.push(seal);
+        self
+    }
+
+    /// Sets seal receiving renomination right, which allows to change the
+    /// asset ticker and name after the issue
+    pub fn renomination_seal(mut self, seal: SealSpec) -> Self {
+        self.renomination_seal = Some(seal);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Adds additional genesis metadata field. Only the fields listed by
+    /// [`FieldType::extra_nomination`] and allowed by the schema genesis are
+    /// accepted by [`Processor::issue`].
+    pub fn field(
+        mut self,
+        field_type: FieldType,
+        value: data::Revealed,
+    ) -> Self {
+        self.extra_fields.insert(field_type, value);
+        self
+    }
+
+    /// Attaches binary content, like a document or its hash, to the asset
+    /// genesis as [`FieldType::Attachment`] field
+    pub fn attachment(self, attachment: Vec<u8>) -> Self {
+        self.field(FieldType::Attachment, data::Revealed::Bytes(attachment))
+    }
+
+    /// Sets blinding factor of the issued supply commitment, which must be a
+    /// valid secp256k1 scalar; by default a random one is used
+    pub fn blinding(mut self, blinding: [u8; 32]) -> Self {
+        self.blinding = Some(blinding);
+        self
+    }
+
+    /// Derives seal and amount blinding factors of the genesis from `seed`,
+    /// so together with [`IssueBuilder::timestamp`] the same parameters
+    /// always produce the same genesis. Intended for test vectors only: the
+    /// seed must be kept secret, since it reveals the allocated seals.
+    pub fn blinding_seed(mut self, seed: [u8; 32]) -> Self {
+        self.blinding_seed = Some(seed);
+        self
+    }
+
+    /// Sets whether [`Processor::issue`] stores the issued asset and reports
+    /// it to the issue observers, which is the default. Callers which must
+    /// register the genesis elsewhere first (like the stash daemon) disable
+    /// it and store the asset only once the genesis is accepted.
+    pub fn store(mut self, store: bool) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Issues the asset with `processor` (see [`Processor::issue`]),
+    /// failing if the required parameters are missing or inconsistent
+    #[inline]
+    pub fn build(
+        self,
+        processor: &mut Processor,
+    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
+        processor.issue(self)
+    }
+
+    /// Checks that the required parameters are set and inflation parameters
+    /// are consistent, producing arguments for [`Processor::issue`]
+    pub fn spec(self) -> Result<IssueSpec, ServiceErrorDomain> {
+        if self.ticker.is_empty() {
+            Err(ServiceErrorDomain::Schema(s!("Asset ticker is not set")))?
+        }
+        if self.name.is_empty() {
+            Err(ServiceErrorDomain::Schema(s!("Asset name is not set")))?
+        }
+        let issue_structure = match (self.max_supply, self.reissue_control) {
+            (None, None) => IssueStructure::SingleIssue,
+            (Some(max_supply), Some(reissue_control)) => {
+                IssueStructure::MultipleIssues {
+                    max_supply,
+                    reissue_control,
+                    inflation_not_before: self.inflation_not_before,
+                }
+            }
+            (None, Some(_)) => Err(ServiceErrorDomain::Schema(s!(
+                "Reissue control seal is given for a single-issue asset \
+                 without max supply"
+            )))?,
+            (Some(_), None) => Err(ServiceErrorDomain::Schema(s!(
+                "Max supply is given without reissue control seal"
+            )))?,
+        };
+        if issue_structure == IssueStructure::SingleIssue
+            && self.inflation_not_before.is_some()
+        {
+            Err(ServiceErrorDomain::Schema(s!(
+                "Inflation time lock is given for a single-issue asset"
+            )))?
+        }
+        if issue_structure == IssueStructure::SingleIssue
+            && !self.reissue_shares.is_empty()
+        {
+            Err(ServiceErrorDomain::Schema(s!(
+                "Inflation allowance shares are given for a single-issue asset"
+            )))?
+        }
+        let blinding = match self.blinding {
+            None => None,
+            Some(blinding) => Some(
+                secp256k1zkp::key::SecretKey::from_slice(
+                    &secp256k1zkp::Secp256k1::new(),
+                    &blinding,
+                )
+                .map_err(|_| {
+                    ServiceErrorDomain::Schema(s!(
+                        "Issue blinding factor is not a valid secp256k1 scalar"
+                    ))
+                })?,
+            ),
+        };
+        Ok(IssueSpec {
+            network: self.network,
+            ticker: self.ticker,
+            name: self.name,
+            description: self.description,
+            issue_structure,
+            allocations: self.allocations,
+            precision: self.precision,
+            prune_seals: self.prune_seals,
+            timestamp: self.timestamp,
+            extra_fields: self.extra_fields,
+            blinding,
+            blinding_seed: self.blinding_seed,
+            renomination_seal: self.renomination_seal,
+            reissue_shares: self.reissue_shares,
+            confidential_allocations: self.confidential_allocations,
+        })
+    }
+}
+
+impl Processor {
+    /// Constructs processor registering fungible assets schema in the
+    /// `rgb_storage`, unless it is already known to it
+    #[inline]
+    pub fn new(
+        rgb_storage: Arc<Mutex<dyn RgbStorage>>,
+        asset_storage: Arc<Mutex<dyn AssetStorage>>,
+    ) -> Result<Self, BootstrapError> {
+        Self::with_schema(rgb_storage, asset_storage, schema::schema())
+    }
+
+    /// Constructs processor issuing and validating assets against `schema`,
+    /// which must be structurally compatible with the fungible assets schema
+    /// (see [`schema::check_compatibility`]), and registers it in the
+    /// `rgb_storage`, unless it is already known to it. Use
+    /// [`schema::schema_v2`] to issue assets with additional genesis fields
+    /// and prune seals.
+    pub fn with_schema(
+        rgb_storage: Arc<Mutex<dyn RgbStorage>>,
+        asset_storage: Arc<Mutex<dyn AssetStorage>>,
+        schema: Schema,
+    ) -> Result<Self, BootstrapError> {
+        debug!("Instantiating RGB asset manager ...");
+
+        schema::check_compatibility(&schema)?;
+        let me = Self {
+            rgb_storage,
+            asset_storage,
+            schema,
+            description_limit: DEFAULT_DESCRIPTION_LIMIT,
+            dust_limit: None,
+            max_outputs: DEFAULT_MAX_OUTPUTS,
+            chain: None,
+            issue_networks: None,
+            issue_observers: vec![],
+            transfer_observers: vec![],
+        };
+        let mut rgb_storage = me.rgb_storage.lock()?;
+        if !rgb_storage.has_schema(me.schema.schema_id())? {
+            info!("RGB fungible assets schema file not found, creating one");
+            rgb_storage.add_schema(&me.schema)?;
+        }
+        drop(rgb_storage);
+
+        Ok(me)
+    }
+
+    /// Sets maximum length of the asset description (in bytes) accepted by
+    /// [`Processor::issue`]; can't exceed [`DEFAULT_DESCRIPTION_LIMIT`]
+    pub fn set_description_limit(&mut self, limit: usize) {
+        self.description_limit = limit.min(DEFAULT_DESCRIPTION_LIMIT);
+    }
+
+    /// Sets minimal amount (in atomic units) of newly created allocations,
+    /// or disables the dust check with `None` (the default). Allocations of
+    /// zero atoms are rejected regardless of the limit.
+    pub fn set_dust_limit(&mut self, limit: Option<AtomicValue>) {
+        self.dust_limit = limit;
+    }
+
+    /// Minimal amount (in atomic units) of newly created allocations, if the
+    /// dust check is enabled
+    #[inline]
+    pub fn dust_limit(&self) -> Option<AtomicValue> {
+        self.dust_limit
+    }
+
+    /// Returns known allocations of the `asset` below the current dust limit,
+    /// which were created before the limit was raised or received from other
+    /// parties, and are uneconomical to spend one by one
+    pub fn dust_allocations<'asset>(
+        &self,
+        asset: &'asset Asset,
+    ) -> Vec<&'asset Allocation> {
+        asset
+            .allocations_sorted(false)
+            .into_iter()
+            .filter(|alloc| {
+                self.dust_limit
+                    .map(|limit| alloc.value().value < limit)
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Sets maximum number of asset allocations (including change and fee)
+    /// created by a single issue or transfer; the limit can't be zero
+    pub fn set_max_outputs(&mut self, limit: usize) {
+        self.max_outputs = limit.max(1);
+    }
+
+    /// Sets network which assets are expected to be issued on, guarding
+    /// against state transitions mixing assets and outpoints of different
+    /// networks
+    pub fn set_chain(&mut self, chain: bp::Chain) {
+        self.chain = Some(chain);
+    }
+
+    /// Restricts networks which new assets can be issued on, guarding, for
+    /// instance, against accidental mainnet issue from a test setup
+    pub fn set_issue_networks(
+        &mut self,
+        networks: impl IntoIterator<Item = bp::Chain>,
+    ) {
+        self.issue_networks = Some(networks.into_iter().collect());
+    }
+
+    /// Registers `observer` called with each asset issued by the processor
+    /// once it is stored
+    pub fn on_issue(&mut self, observer: IssueObserver) {
+        self.issue_observers.push(observer);
+    }
+
+    /// Registers `observer` called with each transfer state transition
+    /// constructed by the processor
+    pub fn on_transfer(&mut self, observer: TransferObserver) {
+        self.transfer_observers.push(observer);
+    }
+
+    fn notify_issue(&self, asset: &Asset, genesis: &Genesis) {
+        for observer in &self.issue_observers {
+            observer(asset, genesis);
+        }
+    }
+
+    fn notify_transfer(&self, asset: &Asset, transition: &Transition) {
+        for observer in &self.transfer_observers {
+            observer(asset, transition);
+        }
+    }
+
+    /// Maximum asset precision declared by the schema of the issued assets,
+    /// which is [`MAX_PRECISION`] for the fungible assets schema
+    pub fn max_precision(&self) -> u8 {
+        match self.schema.field_types.get(&*FieldType::Precision) {
+            Some(lnpbp::rgb::schema::DataFormat::Unsigned(_, _, max)) => {
+                (*max).min(MAX_PRECISION as u128) as u8
+            }
+            _ => MAX_PRECISION,
+        }
+    }
+
+    /// Minimal genesis timestamp declared by the schema of the issued assets
+    fn min_timestamp(&self) -> i64 {
+        match self.schema.field_types.get(&*FieldType::Timestamp) {
+            Some(lnpbp::rgb::schema::DataFormat::Integer(_, min, _)) => {
+                (*min).max(0) as i64
+            }
+            _ => 0,
+        }
+    }
+
+    /// Id of the RGB-20 schema used for the issued assets
+    #[inline]
+    pub fn schema_id(&self) -> SchemaId {
+        self.schema.schema_id()
+    }
+
+    /// Serializes asset `genesis` for passing to other parties in the given
+    /// `format`; string formats are returned as ASCII bytes
+    pub fn export_genesis(
+        genesis: &Genesis,
+        format: GenesisFormat,
+    ) -> Result<Vec<u8>, ServiceErrorDomain> {
+        Ok(match format {
+            GenesisFormat::StrictEncode => {
+                strict_encoding::strict_encode(genesis).map_err(|err| {
+                    ServiceErrorDomain::Internal(err.to_string())
+                })?
+            }
+            GenesisFormat::Bech32m => bech32m::encode(
+                GENESIS_BECH32M_HRP,
+                &strict_encoding::strict_encode(genesis).map_err(|err| {
+                    ServiceErrorDomain::Internal(err.to_string())
+                })?,
+            )
+            .into_bytes(),
+            #[cfg(feature = "base64")]
+            GenesisFormat::Base64 => base64::encode(
+                strict_encoding::strict_encode(genesis).map_err(|err| {
+                    ServiceErrorDomain::Internal(err.to_string())
+                })?,
+            )
+            .into_bytes(),
+        })
+    }
+
+    /// Parses asset genesis serialized by [`Processor::export_genesis`] in
+    /// the given `format`
+    pub fn parse_genesis(
+        data: &[u8],
+        format: GenesisFormat,
+    ) -> Result<Genesis, ServiceErrorDomain> {
+        let err = |err: &dyn Display| {
+            ServiceErrorDomain::Schema(format!("Can't parse genesis: {}", err))
+        };
+        let binary = match format {
+            GenesisFormat::StrictEncode => data.to_vec(),
+            GenesisFormat::Bech32m => {
+                let s = core::str::from_utf8(data).map_err(|e| err(&e))?;
+                let (hrp, binary) = bech32m::decode(s).map_err(|e| err(&e))?;
+                if hrp != GENESIS_BECH32M_HRP {
+                    Err(err(&format!("unexpected prefix `{}`", hrp)))?
+                }
+                binary
+            }
+            #[cfg(feature = "base64")]
+            GenesisFormat::Base64 => {
+                base64::decode(data).map_err(|e| err(&e))?
+            }
+        };
+        Genesis::strict_decode(&binary[..]).map_err(|e| err(&e))
+    }
+
+    /// Computes processor-local commitment to the `transition` for the given
+    /// `method`: a SHA256 hash of the transition id tagged with the method
+    /// name. The digest is internal to the processor and is not an LNPBP-4
+    /// multi-protocol commitment; it is intended for tracking the transitions
+    /// locally before the stash constructs the actual anchor with
+    /// `Anchor::commit`.
+    pub fn local_commitment(
+        &self,
+        transition: &Transition,
+        method: CommitMethod,
+    ) -> Result<LocalCommitment, ServiceErrorDomain> {
+        use lnpbp::hashes::{sha256, Hash, HashEngine};
+
+        if !self
+            .schema
+            .transitions
+            .contains_key(&transition.transition_type())
+        {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Transition type {} is not defined by the asset schema",
+                transition.transition_type()
+            )))?
+        }
+        let tag: &[u8] = match method {
+            CommitMethod::OpReturn => b"rgb-node:local:opret",
+            CommitMethod::Taproot => b"rgb-node:local:tapret",
+        };
+        let tag = sha256::Hash::hash(tag);
+        let node_id = transition.node_id();
+        let mut engine = sha256::Hash::engine();
+        engine.input(&tag[..]);
+        engine.input(&tag[..]);
+        engine.input(&node_id[..]);
+        Ok(LocalCommitment {
+            method,
+            node_id,
+            digest: sha256::Hash::from_engine(engine).into_inner(),
+        })
+    }
+
+    /// Rejects asset issued on a network other than the expected one
+    fn check_chain(&self, asset: &Asset) -> Result<(), ServiceErrorDomain> {
+        match self.chain {
+            Some(ref chain) if chain != asset.chain() => {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Asset {} is issued on {} network, while {} is expected",
+                    asset.id(),
+                    asset.chain(),
+                    chain
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejects allocation `output` with amount `coins` having more
+    /// fractional digits than allowed by the asset `precision` (like `2.5`
+    /// for indivisible assets), which otherwise would be truncated
+    fn check_precision(
+        &self,
+        precision: u8,
+        coins: DecimalAmount,
+        output: &dyn Display,
+    ) -> Result<(), ServiceErrorDomain> {
+        if coins.exceeds_precision(precision) {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Allocation {} amount {} can't be represented with the asset precision of {} digits",
+                output, coins, precision
+            )))?;
+        }
+        Ok(())
+    }
+
+    /// Rejects allocation `output` with zero amount, or with amount below
+    /// the dust limit if it is set
+    fn check_dust(
+        &self,
+        amount: AtomicValue,
+        output: &dyn Display,
+    ) -> Result<(), ServiceErrorDomain> {
+        if amount == 0 {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Allocation {} has zero amount",
+                output
+            )))?;
+        }
+        match self.dust_limit {
+            Some(limit) if amount < limit => {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Allocation {} of {} atomic units is below the dust limit \
+                     of {}",
+                    output, amount, limit
+                )))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Checks asset nomination data against the schema constraints and the
+    /// shape assumed by most of RGB-20 wallets
+    fn validate_nomination(
+        &self,
+        ticker: &str,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<(), ServiceErrorDomain> {
+        if ticker.is_empty()
+            || ticker.len() > 8
+            || !ticker
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Ticker '{}' must consist of 1 to 8 uppercase ASCII letters or digits",
+                ticker
+            )))?;
+        }
+        // The limit applies to the UTF-8 encoding stored in the genesis
+        if name.is_empty() || name.len() > 256 {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Name must be from 1 to 256 bytes long, while it has {}",
+                name.len()
+            )))?;
+        }
+        if let Some(description) = description {
+            if description.len() > self.description_limit {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Description must not exceed {} bytes, while it has {}",
+                    self.description_limit,
+                    description.len()
+                )))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates issue arguments and constructs the asset genesis without
+    /// persisting the asset
+    fn issue_genesis(
+        &self,
+        spec: IssueSpec,
+    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
+        let preview = self.check_issue(&spec)?;
+        let IssueSpec {
+            network,
+            ticker,
+            name,
+            description,
+            issue_structure,
+            mut allocations,
+            precision,
+            prune_seals,
+            timestamp,
+            extra_fields,
+            blinding,
+            blinding_seed,
+            renomination_seal,
+            reissue_shares,
+            mut confidential_allocations,
+        } = spec;
+        let issued_supply = preview.issued_supply;
+        let mut entropy = IssueEntropy::with(blinding_seed);
+
+        let mut metadata = type_map! {
+            FieldType::Ticker => field!(String, ticker.trim().to_string()),
+            FieldType::Name => field!(String, name),
+            FieldType::Precision => field!(U8, precision),
+            FieldType::Timestamp => field!(I64, timestamp.unwrap_or_else(|| Utc::now().timestamp()))
+        };
+        if let Some(description) = description {
+            metadata
+                .insert(*FieldType::ContractText, field!(String, description));
+        }
+        for (field_type, value) in extra_fields {
+            metadata.insert(*field_type, bset![value]);
+        }
+
+        allocations.sort_by_key(|outcoins| {
+            (
+                outcoins.txid,
+                outcoins.vout,
+                AccountingAmount::transmutate(precision, outcoins.coins),
+            )
+        });
+        let issued_allocations = allocations
+            .into_iter()
+            .map(|outcoins| {
+                (
+                    outcoins.seal_definition_with(entropy.seal_blinding()),
+                    AccountingAmount::transmutate(precision, outcoins.coins),
+                )
+            })
+            .collect();
+        confidential_allocations.sort_by_key(|outcoincealed| {
+            (
+                outcoincealed.seal_confidential,
+                AccountingAmount::transmutate(precision, outcoincealed.coins),
+            )
+        });
+        let confidential_allocations = confidential_allocations
+            .into_iter()
+            .map(|outcoincealed| {
+                (
+                    outcoincealed.seal_confidential,
+                    AccountingAmount::transmutate(
+                        precision,
+                        outcoincealed.coins,
+                    ),
+                )
+            })
+            .collect();
+        let mut owned_rights = BTreeMap::new();
+        let input = value::Revealed {
+            value: issued_supply,
+            blinding: blinding.unwrap_or_else(|| entropy.amount_blinding()),
+        };
+        owned_rights.insert(
+            *OwnedRightsType::Assets,
+            entropy.zero_balanced(
+                input,
+                issued_allocations,
+                confidential_allocations,
+            ),
+        );
+        metadata.insert(*FieldType::IssuedSupply, field!(U64, issued_supply));
+
+        if let IssueStructure::MultipleIssues {
+            reissue_control,
+            inflation_not_before,
+            ..
+        } = issue_structure
+        {
+            if let Some(height) = inflation_not_before {
+                metadata.insert(
+                    *FieldType::InflationNotBefore,
+                    field!(U32, height),
+                );
+            }
+
+            // Inflation right state keeps the amount which can be issued
+            // in the future, so the total supply can be restored from the
+            // genesis without any loss of precision
+            let mut allowance = preview.total_supply - issued_supply;
+            let mut inflation_rights =
+                Vec::with_capacity(reissue_shares.len() + 1);
+            for (seal_spec, share) in reissue_shares {
+                allowance -= share;
+                inflation_rights.push(OwnedState::Revealed {
+                    seal_definition: seal_spec
+                        .seal_definition_with(entropy.seal_blinding()),
+                    assigned_state: data::Revealed::U64(share),
+                });
+            }
+            inflation_rights.insert(
+                0,
+                OwnedState::Revealed {
+                    seal_definition: reissue_control
+                        .seal_definition_with(entropy.seal_blinding()),
+                    assigned_state: data::Revealed::U64(allowance),
+                },
+            );
+            owned_rights.insert(
+                *OwnedRightsType::Inflation,
+                Assignments::CustomData(inflation_rights),
+            );
+        }
+
+        if prune_seals.len() > 0 {
+            owned_rights.insert(
+                *OwnedRightsType::BurnReplace,
+                Assignments::Declarative(
+                    prune_seals
+                        .into_iter()
+                        .map(|seal_spec| OwnedState::Revealed {
+                            seal_definition: seal_spec
+                                .seal_definition_with(entropy.seal_blinding()),
+                            assigned_state: data::Void,
+                        })
+                        .collect(),
+                ),
+            );
+        }
+
+        if let Some(seal_spec) = renomination_seal {
+            owned_rights.insert(
+                *OwnedRightsType::Renomination,
+                Assignments::Declarative(vec![OwnedState::Revealed {
+                    seal_definition: seal_spec
+                        .seal_definition_with(entropy.seal_blinding()),
+                    assigned_state: data::Void,
+                }]),
+            );
+        }
+
+        let genesis = Genesis::with(
+            self.schema_id(),
+            network,
             metadata.into(),
-            parent,
-            assignments,
+            owned_rights,
             bset![],
             vec![],
         );
 
-        Ok(transition)
+        let asset = Asset::from_genesis(genesis.clone(), self.schema_id())?;
+        Ok((asset, genesis))
+    }
+
+    /// Runs all the checks of the issue with the parameters accumulated by
+    /// `builder` without constructing the genesis, returning the supply of
+    /// the asset which would be issued
+    pub fn validate_issue(
+        &self,
+        builder: &IssueBuilder,
+    ) -> Result<IssuancePreview, ServiceErrorDomain> {
+        self.check_issue(&builder.clone().spec()?)
+    }
+
+    fn check_issue(
+        &self,
+        spec: &IssueSpec,
+    ) -> Result<IssuancePreview, ServiceErrorDomain> {
+        let IssueSpec {
+            network,
+            ticker,
+            name,
+            description,
+            issue_structure,
+            allocations,
+            precision,
+            prune_seals,
+            timestamp,
+            extra_fields,
+            renomination_seal,
+            reissue_shares,
+            confidential_allocations,
+            ..
+        } = spec;
+        let precision = *precision;
+        match self.issue_networks {
+            Some(ref networks) if !networks.contains(network) => {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Issue on {} network is not allowed; allowed networks are: {}",
+                    network,
+                    networks
+                        .iter()
+                        .map(bp::Chain::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )))?
+            }
+            _ => {}
+        }
+        self.validate_nomination(ticker.trim(), name, description.as_deref())?;
+        let max_precision = self.max_precision();
+        if precision > max_precision {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Precision {} is out of the range 0..={} allowed by the schema",
+                precision, max_precision
+            )))?;
+        }
+        let genesis_schema = &self.schema.genesis;
+        for field_type in extra_fields.keys() {
+            if !FieldType::extra_nomination().contains(field_type)
+                || !genesis_schema.metadata.contains_key(&**field_type)
+            {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Field {} can't be provided as additional asset metadata",
+                    field_type
+                )))?;
+            }
+        }
+        // Rights and fields introduced by the second schema version can't be
+        // put into the genesis of the assets issued under the first one
+        if !prune_seals.is_empty()
+            && !genesis_schema
+                .owned_rights
+                .contains_key(&*OwnedRightsType::BurnReplace)
+        {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Schema {} does not allow assigning prune seals on issue",
+                self.schema_id()
+            )))?;
+        }
+        if let IssueStructure::MultipleIssues {
+            inflation_not_before: Some(_),
+            ..
+        } = issue_structure
+        {
+            if !genesis_schema
+                .metadata
+                .contains_key(&*FieldType::InflationNotBefore)
+            {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Schema {} does not support time-locked inflation",
+                    self.schema_id()
+                )))?;
+            }
+        }
+        match extra_fields.get(&FieldType::Attachment) {
+            None => {}
+            Some(data::Revealed::Bytes(attachment))
+                if attachment.len() <= MAX_ATTACHMENT_LEN => {}
+            Some(_) => Err(ServiceErrorDomain::Schema(format!(
+                "Asset attachment must be binary data up to {} bytes",
+                MAX_ATTACHMENT_LEN
+            )))?,
+        }
+
+        // Explicit timestamp allows reproducible genesis construction; however
+        // it can't precede the minimum allowed by the schema or be set to the
+        // far future
+        let now = Utc::now().timestamp();
+        let min_timestamp = self.min_timestamp();
+        match timestamp {
+            Some(timestamp)
+                if *timestamp < min_timestamp || *timestamp > now + 2 * 3600 =>
+            {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Issue timestamp {} is either before {} allowed by the schema or more than 2 hours in the future",
+                    timestamp, min_timestamp
+                )))?
+            }
+            _ => {}
+        }
+
+        let count = allocations.len() + confidential_allocations.len();
+        if count > self.max_outputs {
+            Err(ServiceErrorDomain::Schema(format!(
+                "Issue creates {} allocations, exceeding the limit of {}",
+                count, self.max_outputs
+            )))?;
+        }
+        let mut issued_supply = 0u64;
+        for outcoins in allocations {
+            self.check_precision(precision, outcoins.coins, outcoins)?;
+            let amount =
+                AccountingAmount::transmutate(precision, outcoins.coins);
+            self.check_dust(amount, outcoins)?;
+            issued_supply =
+                issued_supply.checked_add(amount).ok_or_else(|| {
+                    ServiceErrorDomain::Schema(format!(
+                        "Issued supply overflow on allocation {}",
+                        outcoins
+                    ))
+                })?;
+        }
+        for outcoincealed in confidential_allocations {
+            self.check_precision(
+                precision,
+                outcoincealed.coins,
+                outcoincealed,
+            )?;
+            let amount =
+                AccountingAmount::transmutate(precision, outcoincealed.coins);
+            self.check_dust(amount, outcoincealed)?;
+            issued_supply =
+                issued_supply.checked_add(amount).ok_or_else(|| {
+                    ServiceErrorDomain::Schema(format!(
+                        "Issued supply overflow on allocation {}",
+                        outcoincealed
+                    ))
+                })?;
+        }
+        // Zero primary issue is allowed only if there is an inflation right
+        // with non-zero allowance, so the supply can be issued later
+        let can_inflate = match issue_structure {
+            IssueStructure::SingleIssue => false,
+            IssueStructure::MultipleIssues { max_supply, .. } => {
+                *max_supply > 0
+            }
+        };
+        if issued_supply == 0 && !can_inflate {
+            Err(ServiceErrorDomain::Schema(s!(
+                "Issued supply must be non-zero unless the asset can be inflated"
+            )))?;
+        }
+
+        // Single seal controlling several rights would require all of them to
+        // be spent together
+        let reissue_control = match issue_structure {
+            IssueStructure::SingleIssue => None,
+            IssueStructure::MultipleIssues {
+                reissue_control, ..
+            } => Some(reissue_control),
+        };
+        let mut right_seals = Vec::<SealSpec>::new();
+        for seal_spec in reissue_control
+            .into_iter()
+            .chain(reissue_shares.iter().map(|(seal_spec, _)| seal_spec))
+            .chain(prune_seals.iter())
+            .chain(renomination_seal.iter())
+        {
+            if right_seals.contains(seal_spec) {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Seal {} is assigned more than one issue right",
+                    seal_spec
+                )))?;
+            }
+            right_seals.push(*seal_spec);
+        }
+
+        let total_supply = match issue_structure {
+            IssueStructure::SingleIssue => issued_supply,
+            IssueStructure::MultipleIssues { max_supply, .. } => {
+                let max_supply = *max_supply;
+                if max_supply < issued_supply {
+                    Err(ServiceErrorDomain::Schema(format!(
+                        "Total supply ({}) should be greater than the issued supply ({})",
+                        max_supply, issued_supply
+                    )))?;
+                }
+                let mut allowance = max_supply - issued_supply;
+                for (seal_spec, share) in reissue_shares {
+                    if *share == 0 {
+                        Err(ServiceErrorDomain::Schema(format!(
+                            "Inflation allowance share of seal {} is zero",
+                            seal_spec
+                        )))?;
+                    }
+                    allowance =
+                        allowance.checked_sub(*share).ok_or_else(|| {
+                            ServiceErrorDomain::Schema(format!(
+                                "Inflation allowance shares exceed the allowance of {} atomic units",
+                                max_supply - issued_supply
+                            ))
+                        })?;
+                }
+                max_supply
+            }
+        };
+
+        Ok(IssuancePreview {
+            issued_supply,
+            total_supply,
+        })
+    }
+
+    /// Adds newly issued or imported asset to the `asset_storage`, failing if
+    /// the asset is already known
+    fn store_asset(
+        asset_storage: &Arc<Mutex<dyn AssetStorage>>,
+        asset: Asset,
+    ) -> Result<(), ServiceErrorDomain> {
+        let mut asset_storage = asset_storage.lock()?;
+        if asset_storage.has_asset(asset.contract_id())? {
+            Err(ServiceErrorDomain::Storage(format!(
+                "Asset {} is already known",
+                asset.contract_id()
+            )))?
+        }
+        asset_storage.add_asset(asset)?;
+        Ok(())
+    }
+
+    /// Issues new asset with the parameters accumulated by `builder` like
+    /// [`Processor::issue`], without blocking the async executor: the
+    /// genesis is constructed inline, while the storage is accessed on the
+    /// blocking thread pool. Results are identical to the sync version.
+    pub async fn issue_async(
+        &mut self,
+        builder: IssueBuilder,
+    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
+        let store = builder.store;
+        let (asset, genesis) = self.issue_genesis(builder.spec()?)?;
+        if !store {
+            return Ok((asset, genesis));
+        }
+        let asset_storage = self.asset_storage.clone();
+        let stored = asset.clone();
+        tokio::task::spawn_blocking(move || {
+            Self::store_asset(&asset_storage, stored)
+        })
+        .await??;
+        self.notify_issue(&asset, &genesis);
+        Ok((asset, genesis))
+    }
+
+    /// Issues new fungible asset (RGB-20 schema-based) creating its genesis.
+    /// The issued supply must be non-zero, with the only exception of assets
+    /// issued with non-zero [`IssueBuilder::max_supply`], which may be issued
+    /// later by spending the inflation right. If the timestamp is not given,
+    /// the current time is used.
+    ///
+    /// Allocations are put into the genesis in canonical order, sorted by
+    /// their outpoint (transaction id, then output number) and then by the
+    /// amount, so the genesis structure does not depend on the order in which
+    /// the caller lists them.
+    ///
+    /// The asset is stored and reported to the issue observers unless
+    /// [`IssueBuilder::store`] is disabled.
+    pub fn issue(
+        &mut self,
+        builder: IssueBuilder,
+    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
+        let store = builder.store;
+        let (asset, genesis) = self.issue_genesis(builder.spec()?)?;
+        if store {
+            Self::store_asset(&self.asset_storage, asset.clone())?;
+            self.notify_issue(&asset, &genesis);
+        }
+        Ok((asset, genesis))
+    }
+
+    /// Imports asset from the `genesis` received from other party, checking
+    /// it against the fungible assets schema, and stores it unless the asset
+    /// is already known
+    pub fn import_asset(
+        &mut self,
+        genesis: Genesis,
+    ) -> Result<Asset, ServiceErrorDomain> {
+        let asset = Asset::import_with_schema(genesis, &self.schema)?;
+        Self::store_asset(&self.asset_storage, asset.clone())?;
+
+        Ok(asset)
+    }
+
+    /// Creates secondary issue state transition spending the inflation right
+    /// assigned to `inflation_input`. The new supply is distributed across
+    /// `new_allocations`, while the rest of the inflation allowance (if any)
+    /// is passed forward to the `next_reissue_control` seal; without it the
+    /// remaining allowance is forfeited. The asset allocations, inflation
+    /// rights and supply are updated accordingly, so subsequent secondary
+    /// issues are checked against the new circulating supply.
+    ///
+    /// Inflation rights of time-locked assets (see
+    /// [`IssueBuilder::inflation_not_before`]) can be exercised only if the
+    /// current block `height` is given and is not below the time lock.
+    pub fn inflate(
+        &mut self,
+        asset: &mut Asset,
+        inflation_input: OutPoint,
+        new_allocations: Vec<Outcoins>,
+        next_reissue_control: Option<SealSpec>,
+        height: Option<u32>,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        self.check_chain(asset)?;
+        if let Some(not_before) = *asset.inflation_not_before() {
+            if height.map(|height| height < not_before).unwrap_or(true) {
+                Err(InflationError::TimeLocked { not_before })?
+            }
+        }
+        let right = *asset
+            .right(&inflation_input, OwnedRightsType::Inflation)
+            .ok_or(InflationError::UnknownInflationRight(inflation_input))?;
+        let allowance = asset
+            .known_inflation()
+            .get(&inflation_input)
+            .ok_or(InflationError::UnknownInflationRight(inflation_input))?
+            .atomic_value();
+
+        let precision = *asset.fractional_bits();
+        let mut issued_supply = 0u64;
+        let mut issued_allocations = Vec::with_capacity(new_allocations.len());
+        for outcoins in new_allocations {
+            self.check_precision(precision, outcoins.coins, &outcoins)?;
+            let amount =
+                AccountingAmount::transmutate(precision, outcoins.coins);
+            self.check_dust(amount, &outcoins)?;
+            issued_supply =
+                issued_supply.checked_add(amount).ok_or_else(|| {
+                    ServiceErrorDomain::Schema(format!(
+                        "Issued supply overflow on allocation {}",
+                        outcoins
+                    ))
+                })?;
+            issued_allocations.push((outcoins.seal_definition(), amount));
+        }
+
+        // Both the allowance of the spent right and the asset max supply
+        // must not be exceeded
+        let available = asset
+            .supply()
+            .max_cap()
+            .atomic_value()
+            .saturating_sub(asset.supply().known_circulating().atomic_value())
+            .min(allowance);
+        if issued_supply > available {
+            Err(InflationError::MaxSupplyExceeded {
+                requested: issued_supply,
+                available,
+            })?
+        }
+
+        let metadata = type_map! {
+            FieldType::IssuedSupply => field!(U64, issued_supply)
+        };
+
+        let mut owned_rights = BTreeMap::new();
+        owned_rights.insert(
+            *OwnedRightsType::Assets,
+            Assignments::zero_balanced(
+                vec![value::Revealed {
+                    value: issued_supply,
+                    blinding: random_blinding(),
+                }],
+                issued_allocations,
+                vec![],
+            ),
+        );
+        if let Some(reissue_control) = next_reissue_control {
+            owned_rights.insert(
+                *OwnedRightsType::Inflation,
+                Assignments::CustomData(vec![OwnedState::Revealed {
+                    seal_definition: reissue_control.seal_definition(),
+                    assigned_state: data::Revealed::U64(
+                        allowance - issued_supply,
+                    ),
+                }]),
+            );
+        }
+
+        let mut parent = ParentOwnedRights::new();
+        parent.insert(
+            *right.node_id(),
+            bmap! { *OwnedRightsType::Inflation => vec![*right.index()] },
+        );
+
+        let transition = Transition::with(
+            *TransitionType::Issue,
+            metadata.into(),
+            parent,
+            owned_rights,
+            bset![],
+            vec![],
+        );
+
+        // Updating asset data with the new state
+        let node_id = transition.node_id();
+        let mut forwarded = None;
+        asset.remove_right(&right);
+        for (index, state) in transition
+            .owned_rights_by_type(*OwnedRightsType::Inflation)
+            .map(|assignments| assignments.to_custom_state())
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+        {
+            if let OwnedState::Revealed {
+                seal_definition: seal::Revealed::TxOutpoint(outpoint_reveal),
+                assigned_state: data::Revealed::U64(allowance),
+            } = state
+            {
+                let outpoint = outpoint_reveal.into();
+                asset.add_right(
+                    outpoint,
+                    node_id,
+                    *OwnedRightsType::Inflation,
+                    index as u16,
+                );
+                forwarded = Some((outpoint, allowance));
+            }
+        }
+        asset.register_inflation(
+            node_id,
+            inflation_input,
+            issued_supply,
+            forwarded,
+        );
+        for (index, state) in transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .map(|assignments| assignments.to_discrete_state())
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+        {
+            if let OwnedState::Revealed {
+                seal_definition: seal::Revealed::TxOutpoint(outpoint_reveal),
+                assigned_state,
+            } = state
+            {
+                asset.add_allocation(
+                    outpoint_reveal.into(),
+                    node_id,
+                    index as u16,
+                    assigned_state,
+                );
+            }
+        }
+
+        Ok(transition)
+    }
+
+    /// Creates proof-of-burn state transition destroying `amount` of the asset
+    /// allocated to `burn_input`, spending one of the known burn & replace
+    /// rights. The `burn_proof` seal receives the renewed burn & replace
+    /// right, while the residual of the burned allocations (if any) goes to
+    /// the `change` seal, in which case burn-and-replace transition type is
+    /// used. The asset allocations, rights and supply are updated
+    /// accordingly.
+    pub fn burn(
+        &mut self,
+        asset: &mut Asset,
+        burn_input: OutPoint,
+        amount: DecimalAmount,
+        burn_proof: Option<SealSpec>,
+        change: Option<SealSpec>,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        self.check_chain(asset)?;
+        let burn_allocations =
+            asset.allocations(&burn_input).cloned().unwrap_or_default();
+        if burn_allocations.is_empty() {
+            Err(BurnError::UnknownBurnInput(burn_input))?
+        }
+        let available = burn_allocations
+            .iter()
+            .fold(0u64, |acc, alloc| acc.saturating_add(alloc.value().value));
+        let precision = *asset.fractional_bits();
+        self.check_precision(precision, amount, &burn_input)?;
+        let burned = amount
+            .checked_atomic_value(precision)
+            .ok_or(BurnError::AmountOverflow)?;
+        if burned == 0 {
+            Err(BurnError::ZeroBurn)?
+        }
+        if burned > available {
+            Err(BurnError::OverBurn {
+                requested: burned,
+                available,
+            })?
+        }
+        let residual = available - burned;
+        let right = *asset
+            .known_rights()
+            .values()
+            .flatten()
+            .find(|right| *right.right_type() == *OwnedRightsType::BurnReplace)
+            .ok_or(BurnError::NoBurnRight)?;
+
+        let metadata = type_map! {
+            FieldType::BurnedSupply => field!(U64, burned),
+            FieldType::BurnUtxo => field!(TxOutPoint, burn_input),
+            FieldType::HistoryProofFormat => field!(U8, *HistoryProofFormat::ProofAbsent)
+        };
+
+        let mut owned_rights = BTreeMap::new();
+        if let Some(burn_proof) = burn_proof {
+            owned_rights.insert(
+                *OwnedRightsType::BurnReplace,
+                Assignments::Declarative(vec![OwnedState::Revealed {
+                    seal_definition: burn_proof.seal_definition(),
+                    assigned_state: data::Void,
+                }]),
+            );
+        }
+        let transition_type = if residual > 0 {
+            let change_seal =
+                change.ok_or(BurnError::NoChangeSeal { residual })?;
+            owned_rights.insert(
+                *OwnedRightsType::Assets,
+                Assignments::zero_balanced(
+                    vec![value::Revealed {
+                        value: residual,
+                        blinding: random_blinding(),
+                    }],
+                    vec![(change_seal.seal_definition(), residual)],
+                    vec![],
+                ),
+            );
+            TransitionType::BurnAndReplace
+        } else {
+            TransitionType::Burn
+        };
+
+        let mut parent = ParentOwnedRights::new();
+        parent.insert(
+            *right.node_id(),
+            bmap! { *OwnedRightsType::BurnReplace => vec![*right.index()] },
+        );
+
+        let transition = Transition::with(
+            *transition_type,
+            metadata.into(),
+            parent,
+            owned_rights,
+            bset![],
+            vec![],
+        );
+
+        Self::apply_burn(asset, burn_input, burn_allocations, &transition);
+        asset.register_burn(burned);
+
+        Ok(transition)
+    }
+
+    /// Updates `asset` state with the burn or burn-and-replace `transition`.
+    /// Burned allocations on `burn_input` are not closed by the transition,
+    /// so they are removed explicitly, while the spent burn & replace right,
+    /// the renewed one and the new allocations are processed by
+    /// [`Asset::apply_transition`]
+    fn apply_burn(
+        asset: &mut Asset,
+        burn_input: OutPoint,
+        burn_allocations: Vec<Allocation>,
+        transition: &Transition,
+    ) {
+        for alloc in burn_allocations {
+            asset.remove_allocation(
+                burn_input,
+                *alloc.node_id(),
+                *alloc.index(),
+                alloc.value().clone(),
+            );
+        }
+        asset.apply_transition(transition);
+    }
+
+    /// Creates burn-and-replace state transition destroying all allocations
+    /// of the asset on `burn_input` and re-issuing the same total to
+    /// `new_allocations`, so the asset supply is not changed. The burn &
+    /// replace right is spent and renewed on the `next_control` seal.
+    pub fn replace(
+        &mut self,
+        asset: &mut Asset,
+        burn_input: OutPoint,
+        new_allocations: Vec<Outcoins>,
+        next_control: SealSpec,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        self.check_chain(asset)?;
+        let burn_allocations =
+            asset.allocations(&burn_input).cloned().unwrap_or_default();
+        if burn_allocations.is_empty() {
+            Err(BurnError::UnknownBurnInput(burn_input))?
+        }
+        let burned = Allocation::sum(&burn_allocations);
+        let right = *asset
+            .known_rights()
+            .values()
+            .flatten()
+            .find(|right| *right.right_type() == *OwnedRightsType::BurnReplace)
+            .ok_or(BurnError::NoBurnRight)?;
+
+        let precision = *asset.fractional_bits();
+        let mut replaced = 0u64;
+        let mut replaced_allocations =
+            Vec::with_capacity(new_allocations.len());
+        for outcoins in new_allocations {
+            self.check_precision(precision, outcoins.coins, &outcoins)?;
+            let amount =
+                AccountingAmount::transmutate(precision, outcoins.coins);
+            self.check_dust(amount, &outcoins)?;
+            replaced = replaced
+                .checked_add(amount)
+                .ok_or(BurnError::AmountOverflow)?;
+            replaced_allocations.push((outcoins.seal_definition(), amount));
+        }
+        if replaced != burned {
+            Err(BurnError::ReplaceMismatch { burned, replaced })?
+        }
+
+        let metadata = type_map! {
+            FieldType::BurnedSupply => field!(U64, burned),
+            FieldType::BurnUtxo => field!(TxOutPoint, burn_input),
+            FieldType::HistoryProofFormat => field!(U8, *HistoryProofFormat::ProofAbsent)
+        };
+
+        let mut owned_rights = BTreeMap::new();
+        owned_rights.insert(
+            *OwnedRightsType::BurnReplace,
+            Assignments::Declarative(vec![OwnedState::Revealed {
+                seal_definition: next_control.seal_definition(),
+                assigned_state: data::Void,
+            }]),
+        );
+        owned_rights.insert(
+            *OwnedRightsType::Assets,
+            Assignments::zero_balanced(
+                vec![value::Revealed {
+                    value: burned,
+                    blinding: random_blinding(),
+                }],
+                replaced_allocations,
+                vec![],
+            ),
+        );
+
+        let mut parent = ParentOwnedRights::new();
+        parent.insert(
+            *right.node_id(),
+            bmap! { *OwnedRightsType::BurnReplace => vec![*right.index()] },
+        );
+
+        let transition = Transition::with(
+            *TransitionType::BurnAndReplace,
+            metadata.into(),
+            parent,
+            owned_rights,
+            bset![],
+            vec![],
+        );
+
+        Self::apply_burn(asset, burn_input, burn_allocations, &transition);
+
+        Ok(transition)
+    }
+
+    /// Creates renomination state transition changing asset ticker and/or
+    /// name, spending the renomination right assigned to
+    /// `renomination_input`. New nomination is validated with the same rules
+    /// as on issue; the renomination right is passed to `next_control`. The
+    /// asset nomination and rights are updated accordingly.
+    pub fn renominate(
+        &mut self,
+        asset: &mut Asset,
+        renomination_input: OutPoint,
+        new_ticker: Option<String>,
+        new_name: Option<String>,
+        next_control: SealSpec,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        self.check_chain(asset)?;
+        if new_ticker.is_none() && new_name.is_none() {
+            Err(RenominationError::NothingToRenominate)?
+        }
+        self.validate_nomination(
+            new_ticker.as_deref().unwrap_or(asset.ticker()),
+            new_name.as_deref().unwrap_or(asset.name()),
+            None,
+        )?;
+
+        let mut metadata = type_map! {};
+        if let Some(ref ticker) = new_ticker {
+            metadata.insert(*FieldType::Ticker, field!(String, ticker.clone()));
+        }
+        if let Some(ref name) = new_name {
+            metadata.insert(*FieldType::Name, field!(String, name.clone()));
+        }
+
+        let transition = self.renomination_transition(
+            asset,
+            renomination_input,
+            metadata,
+            next_control,
+        )?;
+        asset.renominate(new_ticker, new_name);
+
+        Ok(transition)
+    }
+
+    /// Creates renomination state transition revising asset contract text
+    /// (description), spending the renomination right assigned to
+    /// `control_input` and passing it to `next_control`. The text must not
+    /// exceed the description limit. The asset description and rights are
+    /// updated accordingly.
+    pub fn update_contract_text(
+        &mut self,
+        asset: &mut Asset,
+        control_input: OutPoint,
+        new_text: String,
+        next_control: SealSpec,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        self.check_chain(asset)?;
+        self.validate_nomination(
+            asset.ticker(),
+            asset.name(),
+            Some(&new_text),
+        )?;
+
+        let metadata = type_map! {
+            FieldType::ContractText => field!(String, new_text.clone())
+        };
+        let transition = self.renomination_transition(
+            asset,
+            control_input,
+            metadata,
+            next_control,
+        )?;
+        asset.update_description(new_text);
+
+        Ok(transition)
+    }
+
+    /// Creates renomination transition with the given `metadata`, moving the
+    /// renomination right from `control_input` to `next_control` both in the
+    /// transition and in the asset data
+    fn renomination_transition(
+        &self,
+        asset: &mut Asset,
+        control_input: OutPoint,
+        metadata: BTreeMap<usize, BTreeSet<data::Revealed>>,
+        next_control: SealSpec,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        let right = *asset
+            .right(&control_input, OwnedRightsType::Renomination)
+            .ok_or(RenominationError::UnknownRenominationRight(
+                control_input,
+            ))?;
+
+        let owned_rights = type_map! {
+            OwnedRightsType::Renomination =>
+            Assignments::Declarative(vec![OwnedState::Revealed {
+                seal_definition: next_control.seal_definition(),
+                assigned_state: data::Void,
+            }])
+        };
+
+        let mut parent = ParentOwnedRights::new();
+        parent.insert(
+            *right.node_id(),
+            bmap! { *OwnedRightsType::Renomination => vec![*right.index()] },
+        );
+
+        let transition = Transition::with(
+            *TransitionType::Renomination,
+            metadata.into(),
+            parent,
+            owned_rights,
+            bset![],
+            vec![],
+        );
+
+        // Updating asset data with the new state
+        asset.remove_right(&right);
+        if let seal::Revealed::TxOutpoint(outpoint_reveal) =
+            next_control.seal_definition()
+        {
+            asset.add_right(
+                outpoint_reveal.into(),
+                transition.node_id(),
+                *OwnedRightsType::Renomination,
+                0,
+            );
+        }
+
+        Ok(transition)
+    }
+
+    /// Packages transfer `transition` of the `asset` spending `inputs` into
+    /// the request for the stash daemon, which anchors the transition into
+    /// the witness `psbt` and assembles the consignment for the recipient
+    /// from the consumed parent nodes. Blinded seals of the transition
+    /// outputs are exposed in the consignment. Fails if the transition
+    /// closes seals of nodes which are not allocated on the `inputs`.
+    pub fn finalize(
+        &self,
+        asset: &Asset,
+        transition: Transition,
+        inputs: Vec<OutPoint>,
+        psbt: Psbt,
+    ) -> Result<ConsignRequest, ServiceErrorDomain> {
+        let input_nodes = inputs
+            .iter()
+            .filter_map(|seal| asset.allocations(seal))
+            .flatten()
+            .map(|alloc| *alloc.node_id())
+            .collect::<BTreeSet<_>>();
+        for node_id in transition.parent_owned_rights().keys() {
+            if !input_nodes.contains(node_id) {
+                Err(ServiceErrorDomain::Schema(format!(
+                    "Transition {} closes seals of node {}, which is not \
+                     allocated on any of the transfer inputs",
+                    transition.node_id(),
+                    node_id
+                )))?
+            }
+        }
+        let outpoints = transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .map(|assignments| {
+                assignments
+                    .to_discrete_state()
+                    .into_iter()
+                    .filter_map(|state| match state {
+                        OwnedState::ConfidentialSeal {
+                            seal_definition,
+                            ..
+                        }
+                        | OwnedState::Confidential {
+                            seal_definition, ..
+                        } => Some(seal_definition),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(ConsignRequest {
+            contract_id: *asset.id(),
+            inputs,
+            transition,
+            other_transition_ids: bmap![],
+            outpoints,
+            psbt,
+        })
+    }
+
+    /// Creates transfer state transitions for several assets sent together,
+    /// one transition per asset. All assets must be issued on the same
+    /// network, and an input spent by any of the transfers must be spent by
+    /// the transfers of all batch assets having allocations on it, since
+    /// otherwise these allocations would be lost. All the transfers are
+    /// validated before any transition is created, so if any of them fails,
+    /// no transitions are returned and none of the assets is changed.
+    /// Otherwise each asset is updated with its transition like with
+    /// [`Asset::apply_transition`], so the spent allocations are removed from
+    /// the known asset state.
+    pub fn transfer_multi(
+        &mut self,
+        transfers: Vec<TransferRequest>,
+    ) -> Result<Vec<Transition>, ServiceErrorDomain> {
+        if let Some(first) = transfers.first() {
+            let chain = first.asset.chain();
+            for TransferRequest { asset, .. } in &transfers {
+                if asset.chain() != chain {
+                    Err(ServiceErrorDomain::Schema(format!(
+                        "Asset {} is issued on {} network, while other assets \
+                         of the batch are issued on {}",
+                        asset.id(),
+                        asset.chain(),
+                        chain
+                    )))?
+                }
+            }
+        }
+        let mut batch = Vec::with_capacity(transfers.len());
+        for request in transfers {
+            let plan = self.validate_transfer(&request)?;
+            batch.push((request, plan));
+        }
+        let spent = batch
+            .iter()
+            .flat_map(|(_, plan)| plan.consumed())
+            .collect::<BTreeSet<_>>();
+        for (request, plan) in &batch {
+            let consumed = plan.consumed();
+            for outpoint in &spent {
+                if !consumed.contains(outpoint)
+                    && request.asset.balance(&[*outpoint]) > 0
+                {
+                    Err(TransferError::UnspentSharedSeal(*outpoint))?
+                }
+            }
+        }
+
+        Ok(batch
+            .into_iter()
+            .map(|(request, plan)| {
+                let TransferRequest { asset, change, .. } = request;
+                if let Some(ChangeSeal::Blinded(reveal)) = change {
+                    asset.add_reveal(reveal);
+                }
+                let transition = self.transfer_planned(plan).transition;
+                asset.apply_transition(&transition);
+                self.notify_transfer(asset, &transition);
+                transition
+            })
+            .collect())
+    }
+
+    /// Creates transfer state transition merging all allocations on the
+    /// `seal` into a single allocation on `new_seal`, preserving their total
+    /// value
+    #[inline]
+    pub fn consolidate_tx(
+        &mut self,
+        asset: &mut Asset,
+        seal: OutPoint,
+        new_seal: SealSpec,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        // The whole input goes to the change seal as a single allocation
+        let request = TransferRequest::new(asset, vec![seal], vec![], vec![])
+            .change_seal(new_seal);
+        Ok(self.transfer(request)?.transition)
+    }
+
+    /// Creates transfer state transition spending a part of allocations on
+    /// the `input` outpoint to the `spend` output, while the remainder is
+    /// assigned back to `change_seal`. The change amount is computed from the
+    /// known allocations, so the transition is always balanced; spending the
+    /// whole input leaves no change.
+    pub fn split_allocation(
+        &mut self,
+        asset: &mut Asset,
+        input: OutPoint,
+        spend: Outcoincealed,
+        change_seal: SealSpec,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        let available = asset.balance(&[input]);
+        let required = AccountingAmount::transmutate(
+            *asset.fractional_bits(),
+            spend.coins,
+        );
+        if required > available {
+            Err(TransferError::InsufficientFunds {
+                required,
+                available,
+            })?
+        }
+        let request =
+            TransferRequest::new(asset, vec![input], vec![], vec![spend])
+                .change_seal(change_seal);
+        Ok(self.transfer(request)?.transition)
+    }
+
+    /// Checks state `transition` of the `asset` against the asset schema
+    /// before it is broadcasted, so malformed transitions are not passed into
+    /// consignments. Verifies that transition metadata fields, assigned and
+    /// closed owned rights are allowed by the schema for the transition type,
+    /// and that the revealed amounts of transfer inputs, known from the asset
+    /// allocations, are equal to the transfer outputs. Validation of the
+    /// state evolution scripts is left to the consignment validation.
+    pub fn validate_transition(
+        &self,
+        asset: &Asset,
+        transition: &Transition,
+    ) -> Result<(), ServiceErrorDomain> {
+        let schema = &self.schema;
+        let transition_type = transition.transition_type();
+        let transition_schema = schema
+            .transitions
+            .get(&transition_type)
+            .ok_or(ValidationError::UnknownTransitionType(transition_type))?;
+
+        for field_type in transition.metadata().keys() {
+            if !transition_schema.metadata.contains_key(field_type) {
+                Err(ValidationError::UnexpectedField {
+                    transition_type,
+                    field_type: *field_type,
+                })?
+            }
+        }
+        for right_type in transition.owned_rights().keys() {
+            if !transition_schema.owned_rights.contains_key(right_type) {
+                Err(ValidationError::UnexpectedOwnedRights {
+                    transition_type,
+                    right_type: *right_type,
+                })?
+            }
+        }
+        let mut inputs = 0u64;
+        for (node_id, closed) in transition.parent_owned_rights() {
+            for (right_type, indexes) in closed {
+                if !transition_schema.closes.contains_key(right_type) {
+                    Err(ValidationError::UnexpectedClosedRights {
+                        transition_type,
+                        right_type: *right_type,
+                    })?
+                }
+                if *right_type != *OwnedRightsType::Assets {
+                    continue;
+                }
+                for index in indexes {
+                    let alloc = asset
+                        .known_allocations()
+                        .values()
+                        .flatten()
+                        .find(|alloc| {
+                            alloc.node_id() == node_id && alloc.index() == index
+                        })
+                        .ok_or(ValidationError::UnknownParentAllocation {
+                            node_id: *node_id,
+                            index: *index,
+                        })?;
+                    inputs = inputs.saturating_add(alloc.value().value);
+                }
+            }
+        }
+
+        if transition_type == *TransitionType::Transfer {
+            let mut outputs = 0u64;
+            for state in transition
+                .owned_rights_by_type(*OwnedRightsType::Assets)
+                .map(|assignments| assignments.to_discrete_state())
+                .unwrap_or_default()
+            {
+                let value = match state {
+                    OwnedState::Revealed { assigned_state, .. }
+                    | OwnedState::ConfidentialSeal { assigned_state, .. } => {
+                        assigned_state.value
+                    }
+                    _ => Err(ValidationError::ConcealedAmount)?,
+                };
+                outputs = outputs.saturating_add(value);
+            }
+            if inputs != outputs {
+                Err(ValidationError::Unbalanced { inputs, outputs })?
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns total balance of the asset in atomic units across all known
+    /// seals. Only allocations with revealed amounts are known to the asset,
+    /// so confidential allocations are not accounted for.
+    #[inline]
+    pub fn balance(&self, asset: &Asset) -> AtomicValue {
+        asset.total_balance()
+    }
+
+    /// Returns balance of the asset in atomic units allocated to `seal`; zero
+    /// if there are no known allocations for it.
+    #[inline]
+    pub fn balance_at(&self, asset: &Asset, seal: &OutPoint) -> AtomicValue {
+        asset.balance(&[*seal])
+    }
+
+    /// Finds which of the `candidates` outpoints is hidden behind the
+    /// `blinded` seal of an incoming transfer, by concealing each of them
+    /// with the `blinding` factor given to the payer. Returns `None` if none
+    /// of the candidates match.
+    pub fn match_blinded_seal(
+        &self,
+        blinded: OutpointHash,
+        candidates: Vec<OutPoint>,
+        blinding: u64,
+    ) -> Option<OutPoint> {
+        candidates.into_iter().find(|candidate| {
+            OutpointReveal {
+                blinding,
+                txid: candidate.txid,
+                vout: candidate.vout,
+            }
+            .conceal()
+                == blinded
+        })
+    }
+
+    /// Selects the minimal number of outpoints with known asset allocations
+    /// which cover the `target` amount of asset atomic units. Since all
+    /// allocations assigned to an outpoint are spent at once, the selection
+    /// works with per-outpoint totals and picks the largest ones first:
+    /// this always produces the smallest possible number of inputs.
+    pub fn select_inputs(
+        &self,
+        asset: &Asset,
+        target: AtomicValue,
+    ) -> Result<Vec<OutPoint>, ServiceErrorDomain> {
+        accumulate_inputs(&self.spendable_outpoints(asset), target)
+    }
+
+    /// Selects outpoints with known asset allocations covering the `target`
+    /// amount of asset atomic units according to the `selection` strategy
+    pub fn select_coins(
+        &self,
+        asset: &Asset,
+        target: AtomicValue,
+        selection: &CoinSelection,
+    ) -> Result<Vec<OutPoint>, ServiceErrorDomain> {
+        match selection {
+            CoinSelection::LargestFirst => self.select_inputs(asset, target),
+            CoinSelection::SmallestFirst => {
+                let mut candidates = self.spendable_outpoints(asset);
+                candidates.sort_by(
+                    |(outpoint1, amount1), (outpoint2, amount2)| {
+                        amount1.cmp(amount2).then(outpoint1.cmp(outpoint2))
+                    },
+                );
+                accumulate_inputs(&candidates, target)
+            }
+            CoinSelection::BranchAndBound => {
+                match self.select_inputs_exact(asset, target) {
+                    Some(inputs) => Ok(inputs),
+                    None => self.select_inputs(asset, target),
+                }
+            }
+            CoinSelection::Manual(inputs) => {
+                let available = asset.balance(inputs);
+                if available < target {
+                    Err(TransferError::InsufficientFunds {
+                        required: target,
+                        available,
+                    })?
+                }
+                Ok(inputs.clone())
+            }
+        }
+    }
+
+    /// Searches for a set of outpoints with known asset allocations summing up
+    /// exactly to the `target` amount of asset atomic units, such that the
+    /// transfer does not require a change output. Uses depth-first
+    /// branch-and-bound search limited to [`BNB_MAX_TRIES`] steps; returns
+    /// `None` if no exact match was found.
+    pub fn select_inputs_exact(
+        &self,
+        asset: &Asset,
+        target: AtomicValue,
+    ) -> Option<Vec<OutPoint>> {
+        fn search(
+            candidates: &[(OutPoint, AtomicValue)],
+            remainders: &[AtomicValue],
+            index: usize,
+            target: AtomicValue,
+            selected: &mut Vec<OutPoint>,
+            tries: &mut usize,
+        ) -> bool {
+            if target == 0 {
+                return true;
+            }
+            *tries += 1;
+            if index >= candidates.len()
+                || remainders[index] < target
+                || *tries > BNB_MAX_TRIES
+            {
+                return false;
+            }
+            let (outpoint, amount) = candidates[index];
+            if amount <= target {
+                selected.push(outpoint);
+                if search(
+                    candidates,
+                    remainders,
+                    index + 1,
+                    target - amount,
+                    selected,
+                    tries,
+                ) {
+                    return true;
+                }
+                selected.pop();
+            }
+            search(candidates, remainders, index + 1, target, selected, tries)
+        }
+
+        let candidates = self.spendable_outpoints(asset);
+        // Sum of all amounts starting from a given candidate, used to cut
+        // branches which can't reach the target anymore
+        let mut remainders = vec![0u64; candidates.len()];
+        let mut sum = 0u64;
+        for (index, (_, amount)) in candidates.iter().enumerate().rev() {
+            sum = sum.saturating_add(*amount);
+            remainders[index] = sum;
+        }
+
+        let mut selected = vec![];
+        let mut tries = 0usize;
+        if search(
+            &candidates,
+            &remainders,
+            0,
+            target,
+            &mut selected,
+            &mut tries,
+        ) {
+            Some(selected)
+        } else {
+            None
+        }
+    }
+
+    /// Returns outpoints with non-zero known asset allocations together with
+    /// the allocated amounts, sorted by amount in descending order
+    fn spendable_outpoints(
+        &self,
+        asset: &Asset,
+    ) -> Vec<(OutPoint, AtomicValue)> {
+        let mut candidates = asset
+            .known_allocations()
+            .keys()
+            .map(|outpoint| (*outpoint, self.balance_at(asset, outpoint)))
+            .filter(|(_, amount)| *amount > 0)
+            .collect::<Vec<_>>();
+        // Ties are resolved by outpoint ordering, so the selection is
+        // deterministic
+        candidates.sort_by(|(outpoint1, amount1), (outpoint2, amount2)| {
+            amount2.cmp(amount1).then(outpoint1.cmp(outpoint2))
+        });
+        candidates
+    }
+
+    /// Function creates a fungible asset-specific state transition (i.e. RGB-20
+    /// schema-based) given an asset information, inputs and desired outputs
+    /// collected by the `request`. Outputs in `ours` are assigned to
+    /// revealed seals, which are not required to be controlled by us: a
+    /// recipient may be given a revealed allocation there (e.g. for auditable
+    /// transfers), while `theirs` get allocations on blinded seals. Both may
+    /// be mixed within a transition. If the inputs exceed the outputs, the
+    /// surplus is assigned to the change seal; without change seal inputs and
+    /// outputs must match exactly. See [`TransferRequest`] for the other
+    /// transfer options.
+    ///
+    /// Transfer does not access the asset storage, so unlike issue it does
+    /// not block on storage locks or I/O and may be called from async code
+    /// directly.
+    pub fn transfer(
+        &mut self,
+        request: TransferRequest,
+    ) -> Result<TransferResult, ServiceErrorDomain> {
+        let plan = self.validate_transfer(&request)?;
+        let TransferRequest { asset, change, .. } = request;
+        // The change on a blinded seal can be spent only once the asset
+        // knows the blinding data of the seal
+        if let Some(ChangeSeal::Blinded(reveal)) = change {
+            asset.add_reveal(reveal);
+        }
+        let result = self.transfer_planned(plan);
+        self.notify_transfer(asset, &result.transition);
+        Ok(result)
+    }
+
+    /// Performs all the checks of [`Processor::transfer`] without creating
+    /// the state transition, returning the plan of the transfer for preview
+    pub fn validate_transfer(
+        &self,
+        request: &TransferRequest,
+    ) -> Result<TransferPlan, ServiceErrorDomain> {
+        let inputs = self.transfer_inputs(request)?;
+        self.plan_transfer(request, inputs)
+    }
+
+    /// Resolves the confidential inputs of the `request` and selects the
+    /// inputs with its coin selection strategy, if any
+    fn transfer_inputs(
+        &self,
+        request: &TransferRequest,
+    ) -> Result<Vec<OutPoint>, ServiceErrorDomain> {
+        let asset = &*request.asset;
+        let mut inputs = request.inputs.clone();
+        for seal_confidential in &request.confidential_inputs {
+            inputs.push(asset.reveal_seal(seal_confidential).ok_or(
+                TransferError::UnresolvableConfidentialInput(
+                    *seal_confidential,
+                ),
+            )?);
+        }
+
+        if let Some(selection) = &request.coin_selection {
+            if !inputs.is_empty() {
+                Err(ServiceErrorDomain::Schema(s!(
+                    "Transfer inputs must not be given together with the \
+                     coin selection strategy"
+                )))?
+            }
+            let precision = *asset.fractional_bits();
+            let target = request
+                .ours
+                .iter()
+                .map(|outcoins| outcoins.coins)
+                .chain(
+                    request
+                        .theirs
+                        .iter()
+                        .map(|outcoincealed| outcoincealed.coins),
+                )
+                .chain(request.fee.iter().map(|(_, coins)| *coins))
+                .map(|coins| AccountingAmount::transmutate(precision, coins))
+                .try_fold(0u64, |acc, amount| acc.checked_add(amount))
+                .ok_or(TransferError::OutputOverflow)?;
+            inputs = self.select_coins(asset, target, selection)?;
+            if request.change.is_none() && asset.balance(&inputs) != target {
+                Err(TransferError::ChangeRequired { target })?
+            }
+        }
+
+        if let Some(seal) = inputs
+            .iter()
+            .find(|seal| request.already_spent.contains(*seal))
+        {
+            Err(TransferError::AlreadySpent(*seal))?
+        }
+        Ok(inputs)
+    }
+
+    fn plan_transfer(
+        &self,
+        request: &TransferRequest,
+        inputs: Vec<OutPoint>,
+    ) -> Result<TransferPlan, ServiceErrorDomain> {
+        let asset = &*request.asset;
+        self.check_chain(asset)?;
+
+        // Spending the same outpoint twice would double-count its allocations
+        let mut unique_inputs = BTreeSet::new();
+        if let Some(seal) =
+            inputs.iter().find(|seal| !unique_inputs.insert(*seal))
+        {
+            Err(TransferError::DuplicateInput(*seal))?
+        }
+
+        // Collecting all input allocations
+        let mut input_allocations = Vec::<Allocation>::new();
+        for seal in &inputs {
+            let revealed = asset.allocations(seal);
+            let concealed = asset.concealed_allocations(seal);
+            if revealed.is_none() && concealed.is_none() {
+                Err(TransferError::UnknownInput(*seal))?
+            }
+            let revealed = revealed.cloned().unwrap_or_default();
+            let concealed = concealed.cloned().unwrap_or_default();
+            if revealed.is_empty() && concealed.is_empty() {
+                Err(TransferError::EmptyInput(*seal))?
+            }
+            input_allocations.extend(revealed);
+            for alloc in concealed {
+                let value = request
+                    .reveals
+                    .get(&(*alloc.node_id(), *alloc.index()))
+                    .ok_or(TransferError::MissingReveal {
+                        outpoint: *seal,
+                        index: *alloc.index(),
+                    })?;
+                input_allocations.push(alloc.reveal(value.clone()).ok_or(
+                    TransferError::InvalidReveal {
+                        outpoint: *seal,
+                        index: *alloc.index(),
+                    },
+                )?);
+            }
+        }
+        // Computing sum of inputs
+        let total_inputs = Allocation::sum(&input_allocations);
+
+        let mut total_outputs = 0u64;
+        let mut allocations_ours = Vec::with_capacity(request.ours.len());
+        for outcoins in &request.ours {
+            self.check_precision(
+                *asset.fractional_bits(),
+                outcoins.coins,
+                outcoins,
+            )?;
+            let amount = AccountingAmount::transmutate(
+                *asset.fractional_bits(),
+                outcoins.coins,
+            );
+            self.check_dust(amount, outcoins)?;
+            total_outputs = total_outputs
+                .checked_add(amount)
+                .ok_or(TransferError::OutputOverflow)?;
+            allocations_ours.push((outcoins.seal_definition(), amount));
+        }
+        let mut allocations_theirs = Vec::with_capacity(request.theirs.len());
+        for outcoincealed in &request.theirs {
+            self.check_precision(
+                *asset.fractional_bits(),
+                outcoincealed.coins,
+                outcoincealed,
+            )?;
+            let amount = AccountingAmount::transmutate(
+                *asset.fractional_bits(),
+                outcoincealed.coins,
+            );
+            self.check_dust(amount, outcoincealed)?;
+            total_outputs = total_outputs
+                .checked_add(amount)
+                .ok_or(TransferError::OutputOverflow)?;
+            allocations_theirs.push((outcoincealed.seal_confidential, amount));
+        }
+        let fee = match &request.fee {
+            Some((fee_seal, coins)) => {
+                self.check_precision(
+                    *asset.fractional_bits(),
+                    *coins,
+                    fee_seal,
+                )?;
+                let amount = AccountingAmount::transmutate(
+                    *asset.fractional_bits(),
+                    *coins,
+                );
+                self.check_dust(amount, fee_seal)?;
+                total_outputs = total_outputs
+                    .checked_add(amount)
+                    .ok_or(TransferError::OutputOverflow)?;
+                Some((fee_seal.seal_definition(), amount))
+            }
+            None => None,
+        };
+
+        let blinded_change =
+            matches!(request.change, Some(ChangeSeal::Blinded(_)));
+        let change = match (
+            total_inputs.checked_sub(total_outputs),
+            request.change.clone(),
+        ) {
+            (Some(0), _) => None,
+            (Some(change), Some(ChangeSeal::Revealed(change_seal))) => {
+                self.check_dust(change, &change_seal)?;
+                Some((change_seal.seal_definition(), change))
+            }
+            (Some(change), Some(ChangeSeal::Blinded(reveal))) => {
+                self.check_dust(
+                    change,
+                    &format!("{}:{}", reveal.txid, reveal.vout),
+                )?;
+                Some((seal::Revealed::TxOutpoint(reveal), change))
+            }
+            _ => Err(TransferError::AmountMismatch {
+                available: total_inputs,
+                requested: total_outputs,
+                precision: *asset.fractional_bits(),
+            })?,
+        };
+
+        let count = allocations_ours.len()
+            + allocations_theirs.len()
+            + fee.iter().count()
+            + change.iter().count();
+        if count > self.max_outputs {
+            Err(TransferError::TooManyOutputs {
+                count,
+                limit: self.max_outputs,
+            })?
+        }
+
+        Ok(TransferPlan {
+            inputs: input_allocations,
+            ours: allocations_ours,
+            theirs: allocations_theirs,
+            fee,
+            change,
+            blinded_change,
+            conceal_amounts: request.conceal_amounts,
+        })
+    }
+
+    /// Creates transfer state transition from the plan produced by
+    /// [`Processor::validate_transfer`]
+    fn transfer_planned(&self, plan: TransferPlan) -> TransferResult {
+        let TransferPlan {
+            inputs: input_allocations,
+            ours: mut allocations_ours,
+            theirs: mut allocations_theirs,
+            fee,
+            change,
+            blinded_change,
+            conceal_amounts,
+        } = plan;
+        allocations_ours.extend(fee);
+        match change {
+            Some((seal_definition, amount)) if blinded_change => {
+                allocations_theirs.push((seal_definition.conceal(), amount))
+            }
+            change => allocations_ours.extend(change),
+        }
+
+        let metadata = type_map! {};
+        let input_amounts = input_allocations
+            .iter()
+            .map(|alloc| alloc.value().clone())
+            .collect();
+        let assignments = type_map! {
+            OwnedRightsType::Assets =>
+            Assignments::zero_balanced(input_amounts, allocations_ours, allocations_theirs)
+        };
+
+        let spent = input_allocations
+            .iter()
+            .map(|alloc| {
+                (*alloc.node_id(), OwnedRightsType::Assets, *alloc.index())
+            })
+            .collect::<Vec<_>>();
+        let mut parent = ParentOwnedRights::new();
+        for (node_id, owned_rights_type, index) in &spent {
+            parent
+                .entry(*node_id)
+                .or_insert(bmap! {})
+                .entry(**owned_rights_type)
+                .or_insert(vec![])
+                .push(*index);
+        }
+
+        let mut inputs = Vec::<OutPoint>::new();
+        for alloc in &input_allocations {
+            if !inputs.contains(alloc.outpoint()) {
+                inputs.push(*alloc.outpoint());
+            }
+        }
+
+        let mut transition = Transition::with(
+            *TransitionType::Transfer,
+            metadata.into(),
+            parent,
+            assignments,
+            bset![],
+            vec![],
+        );
+
+        let blindings = transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .map(|assignments| {
+                assignments
+                    .to_discrete_state()
+                    .into_iter()
+                    .filter_map(|state| match state {
+                        OwnedState::ConfidentialSeal {
+                            seal_definition,
+                            assigned_state,
+                        } => Some((seal_definition, assigned_state.blinding)),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut reveals = bmap! {};
+        if conceal_amounts {
+            let mut owned_rights = transition.owned_rights().clone();
+            if let Some(assignments) =
+                owned_rights.get_mut(&*OwnedRightsType::Assets)
+            {
+                let states = assignments
+                    .to_discrete_state()
+                    .into_iter()
+                    .map(|state| match state {
+                        OwnedState::Revealed {
+                            seal_definition,
+                            assigned_state,
+                        } => {
+                            reveals.insert(
+                                seal_definition.conceal(),
+                                assigned_state.clone(),
+                            );
+                            OwnedState::ConfidentialAmount {
+                                seal_definition,
+                                assigned_state: assigned_state.conceal(),
+                            }
+                        }
+                        OwnedState::ConfidentialSeal {
+                            seal_definition,
+                            assigned_state,
+                        } => {
+                            reveals.insert(
+                                seal_definition,
+                                assigned_state.clone(),
+                            );
+                            OwnedState::Confidential {
+                                seal_definition,
+                                assigned_state: assigned_state.conceal(),
+                            }
+                        }
+                        state => state,
+                    })
+                    .collect();
+                *assignments = Assignments::DiscreteFiniteField(states);
+            }
+            transition = Transition::with(
+                transition.transition_type(),
+                transition.metadata().clone(),
+                transition.parent_owned_rights().clone(),
+                owned_rights,
+                bset![],
+                vec![],
+            );
+        }
+
+        TransferResult {
+            transition,
+            inputs,
+            spent,
+            blindings,
+            reveals,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::cache::{
+        CacheError, FileCache, FileCacheConfig, InMemoryAssetStorage,
+        MemoryCache, SqlCache,
+    };
+    use super::*;
+    use crate::DataFormat;
+    use lnpbp::bitcoin::secp256k1::rand::{self, RngCore};
+    use lnpbp::bitcoin::Txid;
+    use lnpbp::hex::FromHex;
+    use std::path::PathBuf;
+
+    fn storage(data_dir: PathBuf) -> Arc<Mutex<FileCache>> {
+        Arc::new(Mutex::new(
+            FileCache::new(FileCacheConfig {
+                data_dir,
+                data_format: DataFormat::Yaml,
+            })
+            .unwrap(),
+        ))
+    }
+
+    fn data_dir() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rgb-fungible-test-{:016x}",
+            rand::thread_rng().next_u64()
+        ))
+    }
+
+    fn processor() -> Processor {
+        Processor::new(
+            Arc::new(Mutex::new(MemoryCache::new())),
+            Arc::new(Mutex::new(MemoryCache::new())),
+        )
+        .unwrap()
+    }
+
+    /// Processor issuing assets under the second version of the schema,
+    /// which allows extra genesis fields and prune seals
+    fn processor_v2() -> Processor {
+        Processor::with_schema(
+            Arc::new(Mutex::new(MemoryCache::new())),
+            Arc::new(Mutex::new(MemoryCache::new())),
+            schema::schema_v2(),
+        )
+        .unwrap()
+    }
+
+    fn txid() -> Txid {
+        Txid::from_hex(
+            "db2f3035e05795d72e2744dc0e88b2f72acbed97ee9a54c2c7f52d426ae05627",
+        )
+        .unwrap()
+    }
+
+    fn outcoins(coins: &str, vout: u32) -> Outcoins {
+        Outcoins {
+            coins: coins.parse().unwrap(),
+            vout,
+            txid: Some(txid()),
+        }
+    }
+
+    fn outpoint(vout: u32) -> OutPoint {
+        OutPoint { txid: txid(), vout }
+    }
+
+    /// Transfer of the `asset` spending the given `inputs`, with the surplus
+    /// going to the `change_seal`
+    fn request<'a>(
+        asset: &'a mut Asset,
+        inputs: Vec<OutPoint>,
+        ours: Vec<Outcoins>,
+        theirs: Vec<Outcoincealed>,
+        change_seal: Option<SealSpec>,
+    ) -> TransferRequest<'a> {
+        let request = TransferRequest::new(asset, inputs, ours, theirs);
+        match change_seal {
+            Some(change_seal) => request.change_seal(change_seal),
+            None => request,
+        }
+    }
+
+    fn transfer(
+        processor: &mut Processor,
+        asset: &mut Asset,
+        inputs: Vec<OutPoint>,
+        ours: Vec<Outcoins>,
+        theirs: Vec<Outcoincealed>,
+        change_seal: Option<SealSpec>,
+    ) -> Result<Transition, ServiceErrorDomain> {
+        processor
+            .transfer(request(asset, inputs, ours, theirs, change_seal))
+            .map(|result| result.transition)
+    }
+
+    /// Creates transfer state transition of the `asset` selecting the inputs
+    /// automatically, returning it together with the selected inputs.
+    /// Without change seal only the inputs matching outputs exactly are used.
+    fn transfer_auto(
+        processor: &mut Processor,
+        asset: &mut Asset,
+        ours: Vec<Outcoins>,
+        theirs: Vec<Outcoincealed>,
+        change_seal: Option<SealSpec>,
+    ) -> Result<(Transition, Vec<OutPoint>), ServiceErrorDomain> {
+        let request = TransferRequest::new(asset, vec![], ours, theirs);
+        let request = match change_seal {
+            Some(change_seal) => request
+                .coin_selection(CoinSelection::LargestFirst)
+                .change_seal(change_seal),
+            None => request.coin_selection(CoinSelection::BranchAndBound),
+        };
+        processor
+            .transfer(request)
+            .map(|result| (result.transition, result.inputs))
+    }
+
+    fn issue(
+        issue_structure: IssueStructure,
+        allocations: Vec<Outcoins>,
+        precision: u8,
+    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
+        issue_ticker("TICK", issue_structure, allocations, precision)
+    }
+
+    fn issue_ticker(
+        ticker: &str,
+        issue_structure: IssueStructure,
+        allocations: Vec<Outcoins>,
+        precision: u8,
+    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
+        let builder = allocations.into_iter().fold(
+            IssueBuilder::new(bp::Chain::Testnet3, ticker, "Test asset")
+                .issue_structure(issue_structure)
+                .precision(precision),
+            IssueBuilder::allocation,
+        );
+        processor().issue(builder)
+    }
+
+    fn issue_inflatable(precision: u8, max_supply: AtomicValue) -> Asset {
+        let (asset, _) = issue(
+            IssueStructure::MultipleIssues {
+                max_supply,
+                reissue_control: SealSpec {
+                    vout: 1,
+                    txid: Some(txid()),
+                },
+                inflation_not_before: None,
+            },
+            vec![outcoins("1", 0)],
+            precision,
+        )
+        .unwrap();
+        asset
+    }
+
+    #[test]
+    fn test_max_supply_u64_boundary() {
+        let asset = issue_inflatable(0, core::u64::MAX);
+        assert_eq!(asset.supply().max_cap().atomic_value(), core::u64::MAX);
+    }
+
+    #[test]
+    fn test_max_supply_exact_cap() {
+        let asset = issue_inflatable(8, 2_100_000_000_000_000);
+        assert_eq!(
+            asset.supply().max_cap().atomic_value(),
+            2_100_000_000_000_000
+        );
+        assert_eq!(
+            *asset.supply().max_cap(),
+            AccountingAmount::from_fractioned_atomic_value(
+                8,
+                2_100_000_000_000_000
+            )
+        );
+    }
+
+    #[test]
+    fn test_issue_random_blinding() {
+        let allocations = vec![outcoins("10", 0)];
+        let (asset1, genesis1) =
+            issue(IssueStructure::SingleIssue, allocations.clone(), 0).unwrap();
+        let (asset2, genesis2) =
+            issue(IssueStructure::SingleIssue, allocations, 0).unwrap();
+
+        let blinding = |asset: &Asset| {
+            asset.allocations(&outpoint(0)).unwrap()[0].value().blinding
+        };
+        assert_ne!(blinding(&asset1), blinding(&asset2));
+        assert_ne!(blinding(&asset1), secp256k1zkp::key::ONE_KEY);
+        assert_ne!(genesis1.contract_id(), genesis2.contract_id());
+    }
+
+    #[test]
+    fn test_issue_supply_overflow() {
+        let result = issue(
+            IssueStructure::SingleIssue,
+            vec![
+                outcoins("10000000000000000000", 0),
+                outcoins("10000000000000000000", 1),
+            ],
+            0,
+        );
+        match result {
+            Err(ServiceErrorDomain::Schema(msg)) => {
+                assert!(msg.contains("overflow"))
+            }
+            _ => panic!("Issued supply overflow is not detected"),
+        }
+    }
+
+    #[test]
+    fn test_transfer_outputs_overflow() {
+        let (mut asset, _) =
+            issue(IssueStructure::SingleIssue, vec![outcoins("10", 0)], 0)
+                .unwrap();
+        let mut processor = processor();
+        let result = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![
+                outcoins("10000000000000000000", 1),
+                outcoins("10000000000000000000", 2),
+            ],
+            vec![],
+            None,
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::OutputOverflow)
+        );
+        assert_eq!(
+            TransferError::OutputOverflow.to_string(),
+            "Output amount overflows 64-bit integer"
+        );
+    }
+
+    fn issue_spread() -> Asset {
+        let (asset, _) = issue(
+            IssueStructure::SingleIssue,
+            vec![outcoins("5", 0), outcoins("3", 1), outcoins("2", 2)],
+            0,
+        )
+        .unwrap();
+        asset
+    }
+
+    #[test]
+    fn test_transfer_auto_exact() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let (_, inputs) = transfer_auto(
+            &mut processor,
+            &mut asset,
+            vec![outcoins("8", 3)],
+            vec![],
+            None,
+        )
+        .unwrap();
+        assert_eq!(inputs, vec![outpoint(0), outpoint(1)]);
+    }
+
+    #[test]
+    fn test_select_inputs_over_coverage() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let inputs = processor.select_inputs(&asset, 6).unwrap();
+        // 5 + 3 covers 6, leaving 2 units which require a change output
+        assert_eq!(inputs, vec![outpoint(0), outpoint(1)]);
+        assert!(transfer_auto(
+            &mut processor,
+            &mut asset,
+            vec![outcoins("6", 3)],
+            vec![],
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_transfer_auto_underfunded() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        assert_eq!(
+            transfer_auto(
+                &mut processor,
+                &mut asset,
+                vec![outcoins("11", 3)],
+                vec![],
+                None
+            )
+            .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::InsufficientFunds {
+                required: 11,
+                available: 10
+            })
+        );
+    }
+
+    fn revealed_outputs(transition: &Transition) -> Vec<(u32, AtomicValue)> {
+        transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .map(|assignments| {
+                assignments
+                    .to_discrete_state()
+                    .into_iter()
+                    .filter_map(|state| match state {
+                        OwnedState::Revealed {
+                            seal_definition: seal::Revealed::TxOutpoint(reveal),
+                            assigned_state,
+                        } => Some((reveal.vout, assigned_state.value)),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn change_seal(vout: u32) -> Option<SealSpec> {
+        Some(SealSpec {
+            vout,
+            txid: Some(txid()),
+        })
+    }
+
+    #[test]
+    fn test_transfer_dust_change() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        processor.set_dust_limit(Some(2));
+        let err = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(1)],
+            vec![outcoins("2", 4)],
+            vec![],
+            change_seal(5),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, ServiceErrorDomain::Schema(ref msg) if msg.contains("vout: 5") && msg.contains("dust limit"))
+        );
+
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("3", 4)],
+            vec![],
+            change_seal(5),
+        )
+        .unwrap();
+        let mut outputs = revealed_outputs(&transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(4, 3), (5, 2)]);
+    }
+
+    #[test]
+    fn test_transfer_change() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("3", 3)],
+            vec![],
+            change_seal(4),
+        )
+        .unwrap();
+        let mut outputs = revealed_outputs(&transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(3, 3), (4, 2)]);
+    }
+
+    #[test]
+    fn test_transfer_no_change() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("5", 3)],
+            vec![],
+            change_seal(4),
+        )
+        .unwrap();
+        assert_eq!(revealed_outputs(&transition), vec![(3, 5)]);
+    }
+
+    #[test]
+    fn test_transfer_underfunded_with_change() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        // Change seal must not make up for the missing inputs
+        assert!(transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("6", 3)],
+            vec![],
+            change_seal(4)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_inflate_max_supply_exceeded() {
+        let mut asset = issue_inflatable(0, 10);
+        let mut processor = processor();
+        let err = processor
+            .inflate(
+                &mut asset,
+                outpoint(1),
+                vec![outcoins("10", 2)],
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Inflation(InflationError::MaxSupplyExceeded {
+                requested: 10,
+                available: 9,
+            })
+        );
+    }
+
+    #[test]
+    fn test_inflate_unknown_right() {
+        let mut asset = issue_inflatable(0, 10);
+        let mut processor = processor();
+        let err = processor
+            .inflate(
+                &mut asset,
+                outpoint(0),
+                vec![outcoins("1", 2)],
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Inflation(
+                InflationError::UnknownInflationRight(outpoint(0))
+            )
+        );
+    }
+
+    #[test]
+    fn test_inflate_passes_right_forward() {
+        let mut asset = issue_inflatable(0, 10);
+        let mut processor = processor();
+        let transition = processor
+            .inflate(
+                &mut asset,
+                outpoint(1),
+                vec![outcoins("4", 2)],
+                change_seal(3),
+                None,
+            )
+            .unwrap();
+        assert_eq!(transition.transition_type(), *TransitionType::Issue);
+        assert_eq!(
+            transition.metadata().u64(*FieldType::IssuedSupply),
+            vec![4]
+        );
+        assert_eq!(revealed_outputs(&transition), vec![(2, 4)]);
+        let inflation = transition
+            .owned_rights_by_type(*OwnedRightsType::Inflation)
+            .unwrap()
+            .known_state_data()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(inflation, vec![data::Revealed::U64(5)]);
+    }
+
+    fn issue_burnable() -> Asset {
+        let (asset, _) =
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                .allocation(outcoins("5", 0))
+                .allocation(outcoins("3", 1))
+                .prune_seal(SealSpec {
+                    vout: 5,
+                    txid: Some(txid()),
+                })
+                .build(&mut processor_v2())
+                .unwrap();
+        asset
+    }
+
+    #[test]
+    fn test_burn_over_burn() {
+        let mut asset = issue_burnable();
+        let mut processor = processor();
+        let err = processor
+            .burn(&mut asset, outpoint(1), "4".parse().unwrap(), None, None)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Burn(BurnError::OverBurn {
+                requested: 4,
+                available: 3,
+            })
+        );
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 8);
+
+        // Amount is neither truncated to the precision nor saturated
+        assert!(matches!(
+            processor.burn(
+                &mut asset,
+                outpoint(1),
+                "1.5".parse().unwrap(),
+                None,
+                change_seal(6)
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert_eq!(
+            processor
+                .burn(
+                    &mut asset,
+                    outpoint(1),
+                    "18446744073709551616".parse().unwrap(),
+                    None,
+                    None
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Burn(BurnError::AmountOverflow)
+        );
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 8);
+    }
+
+    #[test]
+    fn test_burn_change() {
+        let mut asset = issue_burnable();
+        let mut processor = processor();
+        let transition = processor
+            .burn(
+                &mut asset,
+                outpoint(0),
+                "2".parse().unwrap(),
+                change_seal(7),
+                change_seal(6),
+            )
+            .unwrap();
+        assert_eq!(
+            transition.transition_type(),
+            *TransitionType::BurnAndReplace
+        );
+        assert_eq!(
+            transition.metadata().u64(*FieldType::BurnedSupply),
+            vec![2]
+        );
+        assert_eq!(revealed_outputs(&transition), vec![(6, 3)]);
+
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 6);
+        assert_eq!(asset.allocations(&outpoint(0)).map(Vec::len), Some(0));
+        assert_eq!(
+            asset
+                .allocations(&outpoint(6))
+                .unwrap()
+                .iter()
+                .map(|alloc| alloc.value().value)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+        assert!(asset
+            .right(&outpoint(5), OwnedRightsType::BurnReplace)
+            .is_none());
+        assert!(asset
+            .right(&outpoint(6), OwnedRightsType::BurnReplace)
+            .is_none());
+        assert!(asset
+            .right(&outpoint(7), OwnedRightsType::BurnReplace)
+            .is_some());
+
+        // Residual requires change seal
+        assert_eq!(
+            processor
+                .burn(
+                    &mut asset,
+                    outpoint(1),
+                    "1".parse().unwrap(),
+                    change_seal(8),
+                    None,
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Burn(BurnError::NoChangeSeal { residual: 2 })
+        );
+    }
+
+    #[test]
+    fn test_burn_whole_allocation() {
+        let mut asset = issue_burnable();
+        let mut processor = processor();
+        let transition = processor
+            .burn(&mut asset, outpoint(1), "3".parse().unwrap(), None, None)
+            .unwrap();
+        assert_eq!(transition.transition_type(), *TransitionType::Burn);
+        assert!(revealed_outputs(&transition).is_empty());
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 5);
+    }
+
+    #[test]
+    fn test_burn_supply_invariant() {
+        let mut asset = issue_burnable();
+        let mut processor = processor();
+        assert_eq!(
+            processor
+                .burn(&mut asset, outpoint(0), "0".parse().unwrap(), None, None)
+                .unwrap_err(),
+            ServiceErrorDomain::Burn(BurnError::ZeroBurn)
+        );
+
+        // Burn & replace followed by the burn of the replaced allocation with
+        // the renewed right
+        processor
+            .burn(
+                &mut asset,
+                outpoint(0),
+                "4".parse().unwrap(),
+                change_seal(7),
+                change_seal(6),
+            )
+            .unwrap();
+        assert_eq!(
+            asset.supply().known_circulating().atomic_value(),
+            processor.balance(&asset)
+        );
+        processor
+            .burn(&mut asset, outpoint(6), "1".parse().unwrap(), None, None)
+            .unwrap();
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 3);
+        assert_eq!(processor.balance(&asset), 3);
+        assert_eq!(
+            processor
+                .burn(&mut asset, outpoint(1), "1".parse().unwrap(), None, None)
+                .unwrap_err(),
+            ServiceErrorDomain::Burn(BurnError::NoBurnRight)
+        );
+    }
+
+    #[test]
+    fn test_balance() {
+        let (asset, _) = issue(
+            IssueStructure::SingleIssue,
+            vec![outcoins("5", 0), outcoins("2", 0), outcoins("3", 1)],
+            0,
+        )
+        .unwrap();
+        let processor = processor();
+        assert_eq!(processor.balance(&asset), 10);
+        assert_eq!(processor.balance_at(&asset, &outpoint(0)), 7);
+        assert_eq!(processor.balance_at(&asset, &outpoint(1)), 3);
+        assert_eq!(processor.balance_at(&asset, &outpoint(2)), 0);
+    }
+
+    #[test]
+    fn test_transfer_auto_change() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let (transition, inputs) = transfer_auto(
+            &mut processor,
+            &mut asset,
+            vec![outcoins("6", 3)],
+            vec![],
+            change_seal(4),
+        )
+        .unwrap();
+        assert_eq!(inputs, vec![outpoint(0), outpoint(1)]);
+        let mut outputs = revealed_outputs(&transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(3, 6), (4, 2)]);
+    }
+
+    #[test]
+    fn test_transfer_auto_exact_match() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        // Largest-first selection would pick 5 + 3, requiring change
+        let (transition, inputs) = transfer_auto(
+            &mut processor,
+            &mut asset,
+            vec![outcoins("7", 3)],
+            vec![],
+            None,
+        )
+        .unwrap();
+        assert_eq!(inputs, vec![outpoint(0), outpoint(2)]);
+        assert_eq!(revealed_outputs(&transition), vec![(3, 7)]);
+        assert_eq!(
+            transfer_auto(
+                &mut processor,
+                &mut asset,
+                vec![outcoins("6", 3)],
+                vec![],
+                None
+            )
+            .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::ChangeRequired {
+                target: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_issue_zero_supply() {
+        assert!(matches!(
+            issue(IssueStructure::SingleIssue, vec![], 0),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert!(matches!(
+            issue(IssueStructure::SingleIssue, vec![outcoins("0", 0)], 0),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        let (asset, _) = issue(
+            IssueStructure::MultipleIssues {
+                max_supply: 100,
+                reissue_control: SealSpec {
+                    vout: 1,
+                    txid: Some(txid()),
+                },
+                inflation_not_before: None,
+            },
+            vec![],
+            0,
+        )
+        .unwrap();
+        assert_eq!(asset.supply().max_cap().atomic_value(), 100);
+    }
+
+    #[test]
+    fn test_issue_persistence() {
+        let data_dir = data_dir();
+        let rgb_storage = Arc::new(Mutex::new(MemoryCache::new()));
+        let mut processor =
+            Processor::new(rgb_storage.clone(), storage(data_dir.clone()))
+                .unwrap();
+        let (asset, _) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                    .allocation(outcoins("10", 0)),
+            )
+            .unwrap();
+        drop(processor);
+
+        let storage = storage(data_dir.clone());
+        let _processor = Processor::new(rgb_storage, storage.clone()).unwrap();
+        assert_eq!(storage.lock().unwrap().asset(*asset.id()).unwrap(), asset);
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_issue_ticker() {
+        for ticker in &["tick", "TICKERTOO", "", "  ", "TI-K"] {
+            assert!(matches!(
+                issue_ticker(
+                    ticker,
+                    IssueStructure::SingleIssue,
+                    vec![outcoins("1", 0)],
+                    0
+                ),
+                Err(ServiceErrorDomain::Schema(_))
+            ));
+        }
+        let (asset, _) = issue_ticker(
+            " TICK8 ",
+            IssueStructure::SingleIssue,
+            vec![outcoins("1", 0)],
+            0,
+        )
+        .unwrap();
+        assert_eq!(asset.ticker(), "TICK8");
+    }
+
+    fn issue_at(
+        timestamp: i64,
+    ) -> Result<(Asset, Genesis), ServiceErrorDomain> {
+        processor().issue(
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                .allocation(outcoins("10", 0))
+                .timestamp(timestamp)
+                .blinding_seed([0x2a; 32]),
+        )
+    }
+
+    #[test]
+    fn test_issue_timestamp() {
+        let (asset1, genesis1) = issue_at(1602340666).unwrap();
+        let (asset2, genesis2) = issue_at(1602340666).unwrap();
+        // Blinding factors are derived from the same seed, so the genesis
+        // depends only on the timestamp
+        assert_eq!(genesis1.metadata(), genesis2.metadata());
+        assert_eq!(
+            genesis1.metadata().i64(*FieldType::Timestamp),
+            vec![1602340666]
+        );
+        assert_eq!(asset1.date(), asset2.date());
+        assert_eq!(genesis1.contract_id(), genesis2.contract_id());
+        let (asset3, genesis3) = issue_at(1602340667).unwrap();
+        assert_ne!(asset3.date(), asset1.date());
+        assert_ne!(genesis3.contract_id(), genesis1.contract_id());
+
+        assert!(matches!(issue_at(-1), Err(ServiceErrorDomain::Schema(_))));
+        // Timestamps before the schema minimum would produce invalid genesis
+        assert!(matches!(issue_at(0), Err(ServiceErrorDomain::Schema(_))));
+        assert!(matches!(
+            issue_at(1602340665),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert!(matches!(
+            issue_at(Utc::now().timestamp() + 3 * 3600),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_nomination() {
+        let nominate = |processor: &mut Processor,
+                        ticker: &str,
+                        name: String,
+                        description: Option<String>| {
+            let builder = IssueBuilder::with_network(bp::Chain::Testnet3)
+                .ticker(ticker)
+                .name(&name)
+                .allocation(outcoins("1", 0));
+            processor.issue(match description {
+                Some(description) => builder.description(&description),
+                None => builder,
+            })
+        };
+        let mut processor = processor();
+        for (ticker, name) in &[
+            ("", s!("Test asset")),
+            ("TÍCK", s!("Test asset")),
+            ("TICK", s!("")),
+            ("TICK", "a".repeat(257)),
+            // Two bytes per character exceed the limit
+            ("TICK", "ä".repeat(129)),
+        ] {
+            assert!(matches!(
+                nominate(&mut processor, ticker, name.clone(), None),
+                Err(ServiceErrorDomain::Schema(_))
+            ));
+        }
+        assert!(nominate(&mut processor, "TICK", "ä".repeat(128), None).is_ok());
+
+        processor.set_description_limit(4);
+        assert!(
+            nominate(&mut processor, "TICK", s!("Test"), Some(s!("Test")))
+                .is_ok()
+        );
+        assert!(matches!(
+            nominate(&mut processor, "TICK", s!("Test"), Some(s!("Tests"))),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = Arc::new(Mutex::new(MemoryCache::new()));
+        let mut processor = Processor::new(
+            Arc::new(Mutex::new(MemoryCache::new())),
+            storage.clone(),
+        )
+        .unwrap();
+        let (asset, _) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                    .allocation(outcoins("10", 0)),
+            )
+            .unwrap();
+
+        let mut storage = storage.lock().unwrap();
+        assert!(storage.has_asset(*asset.id()).unwrap());
+        assert_eq!(storage.assets().unwrap(), vec![asset.clone()]);
+        assert_eq!(storage.asset(*asset.id()).unwrap(), asset);
+        // Re-adding the asset replaces it
+        assert!(storage.add_asset(asset.clone()).unwrap());
+        assert!(storage.remove_asset(*asset.id()).unwrap());
+        assert!(!storage.has_asset(*asset.id()).unwrap());
+        assert!(storage.asset(*asset.id()).is_err());
+        // Removing unknown asset is not an error
+        assert!(!storage.remove_asset(*asset.id()).unwrap());
+    }
+
+    #[test]
+    fn test_memory_storage_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MemoryCache>();
+        assert_send_sync::<InMemoryAssetStorage>();
+        assert_send_sync::<Arc<Mutex<dyn AssetStorage>>>();
+    }
+
+    #[test]
+    fn test_cache_asset_storage() {
+        fn assert_asset_storage<T: AssetStorage>() {}
+        assert_asset_storage::<FileCache>();
+        assert_asset_storage::<SqlCache>();
+    }
+
+    #[test]
+    fn test_in_memory_asset_storage() {
+        let storage = Arc::new(Mutex::new(InMemoryAssetStorage::new()));
+        let mut processor = Processor::new(
+            Arc::new(Mutex::new(MemoryCache::new())),
+            storage.clone(),
+        )
+        .unwrap();
+        let (asset, genesis) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                    .allocation(outcoins("10", 0)),
+            )
+            .unwrap();
+        // Storing the same asset twice is rejected by the processor
+        assert!(processor.import_asset(genesis).is_err());
+        let foreign_schema = schema::schema_v2();
+
+        let mut storage = storage.lock().unwrap();
+        assert_eq!(storage.asset(*asset.id()).unwrap(), asset);
+        assert_eq!(
+            storage
+                .assets_by_schema(schema::schema().schema_id())
+                .unwrap(),
+            vec![asset.clone()]
+        );
+        assert!(storage
+            .assets_by_schema(foreign_schema.schema_id())
+            .unwrap()
+            .is_empty());
+        // Duplicate insert replaces the stored asset
+        assert!(storage.add_asset(asset.clone()).unwrap());
+        assert_eq!(storage.assets().unwrap().len(), 1);
+        assert!(storage.remove_asset(*asset.id()).unwrap());
+        assert!(!storage.has_asset(*asset.id()).unwrap());
+        assert!(storage.asset(*asset.id()).is_err());
+        assert!(!storage.remove_asset(*asset.id()).unwrap());
+    }
+
+    #[derive(Default)]
+    struct CountingStorage {
+        inner: MemoryCache,
+        additions: usize,
+    }
+
+    impl RgbStorage for CountingStorage {
+        fn has_schema(&self, id: SchemaId) -> Result<bool, CacheError> {
+            self.inner.has_schema(id)
+        }
+
+        fn add_schema(&mut self, schema: &Schema) -> Result<bool, CacheError> {
+            self.additions += 1;
+            self.inner.add_schema(schema)
+        }
+    }
+
+    #[test]
+    fn test_schema_registration() {
+        let rgb_storage = Arc::new(Mutex::new(CountingStorage::default()));
+        let asset_storage = Arc::new(Mutex::new(MemoryCache::new()));
+
+        Processor::new(rgb_storage.clone(), asset_storage.clone()).unwrap();
+        assert_eq!(rgb_storage.lock().unwrap().additions, 1);
+        assert!(rgb_storage
+            .lock()
+            .unwrap()
+            .has_schema(schema::schema().schema_id())
+            .unwrap());
+
+        // Second run must find the schema already registered
+        Processor::new(rgb_storage.clone(), asset_storage).unwrap();
+        assert_eq!(rgb_storage.lock().unwrap().additions, 1);
+    }
+
+    struct FailingStorage;
+
+    impl RgbStorage for FailingStorage {
+        fn has_schema(&self, _: SchemaId) -> Result<bool, CacheError> {
+            Ok(false)
+        }
+
+        fn add_schema(&mut self, _: &Schema) -> Result<bool, CacheError> {
+            Err(CacheError::Io(s!("Storage is read-only")))
+        }
+    }
+
+    #[test]
+    fn test_schema_registration_failure() {
+        assert!(Processor::new(
+            Arc::new(Mutex::new(FailingStorage)),
+            Arc::new(Mutex::new(MemoryCache::new())),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_transfer_amount_mismatch() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        assert_eq!(
+            transfer(
+                &mut processor,
+                &mut asset,
+                vec![outpoint(0)],
+                vec![outcoins("6", 3)],
+                vec![],
+                None
+            )
+            .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::AmountMismatch {
+                available: 5,
+                requested: 6,
+                precision: 0,
+            })
+        );
+        assert!(TransferError::AmountMismatch {
+            available: 150,
+            requested: 275,
+            precision: 2,
+        }
+        .to_string()
+        .starts_with(
+            "Input amount of 150 atomic units is not equal to output amount"
+        ));
+    }
+
+    #[test]
+    fn test_transfer_unknown_input() {
+        let mut asset = issue_burnable();
+        let mut processor = processor();
+        let transfer =
+            |processor: &mut Processor, asset: &mut Asset, input: OutPoint| {
+                transfer(
+                    processor,
+                    asset,
+                    vec![input],
+                    vec![outcoins("1", 3)],
+                    vec![],
+                    None,
+                )
+                .unwrap_err()
+            };
+        let err = transfer(&mut processor, &mut asset, outpoint(4));
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Transfer(TransferError::UnknownInput(
+                outpoint(4)
+            ))
+        );
+        assert_eq!(
+            TransferError::UnknownInput(outpoint(4)).to_string(),
+            format!("Unknown input {}", outpoint(4))
+        );
+
+        // Burn leaves the outpoint known, but without allocations
+        processor
+            .burn(&mut asset, outpoint(1), "3".parse().unwrap(), None, None)
+            .unwrap();
+        let err = transfer(&mut processor, &mut asset, outpoint(1));
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Transfer(TransferError::EmptyInput(outpoint(
+                1
+            )))
+        );
+        assert_ne!(
+            TransferError::EmptyInput(outpoint(1)).to_string(),
+            TransferError::UnknownInput(outpoint(1)).to_string()
+        );
+    }
+
+    #[test]
+    fn test_transfer_confidential_input() {
+        use lnpbp::bp::blind::OutpointReveal;
+        use lnpbp::client_side_validation::Conceal;
+
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let reveal = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 7,
+        };
+        let received = value::Revealed {
+            value: 4,
+            blinding: random_blinding(),
+        };
+        let node_id = *asset.allocations(&outpoint(0)).unwrap()[0].node_id();
+        // Simulating allocation received to the blinded seal
+        asset.add_confidential_allocation(reveal.clone(), node_id, 3, received);
+
+        let transition = processor
+            .transfer(
+                TransferRequest::new(
+                    &mut asset,
+                    vec![],
+                    vec![outcoins("4", 3)],
+                    vec![],
+                )
+                .confidential_input(reveal.conceal()),
+            )
+            .unwrap()
+            .transition;
+        assert_eq!(revealed_outputs(&transition), vec![(3, 4)]);
+        assert_eq!(
+            transition
+                .parent_owned_rights()
+                .get(&node_id)
+                .and_then(|rights| rights.get(&*OwnedRightsType::Assets))
+                .cloned(),
+            Some(vec![3])
+        );
+
+        let unknown = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 8,
+        }
+        .conceal();
+        assert_eq!(
+            processor
+                .transfer(
+                    TransferRequest::new(
+                        &mut asset,
+                        vec![],
+                        vec![outcoins("4", 3)],
+                        vec![],
+                    )
+                    .confidential_input(unknown),
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Transfer(
+                TransferError::UnresolvableConfidentialInput(unknown)
+            )
+        );
+    }
+
+    #[test]
+    fn test_asset_nomination() {
+        let (asset, _) = processor()
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                    .description("Asset description")
+                    .allocation(outcoins("1.5", 0))
+                    .allocation(outcoins("2", 1))
+                    .precision(2)
+                    .timestamp(1602340666),
+            )
+            .unwrap();
+        assert_eq!(asset.ticker(), "TICK");
+        assert_eq!(asset.name(), "Test asset");
+        assert_eq!(asset.description().as_deref(), Some("Asset description"));
+        assert_eq!(asset.precision(), 2);
+        assert_eq!(asset.created_at().timestamp(), 1602340666);
+        assert_eq!(asset.issued_supply(), 350);
+    }
+
+    #[test]
+    fn test_transfer_concealed_input() {
+        use lnpbp::client_side_validation::Conceal;
+
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let node_id = *asset.allocations(&outpoint(0)).unwrap()[0].node_id();
+        let secret = value::Revealed {
+            value: 4,
+            blinding: random_blinding(),
+        };
+        asset.add_concealed_allocation(
+            outpoint(7),
+            node_id,
+            3,
+            secret.conceal(),
+        );
+
+        let mut transfer = |reveal: Option<value::Revealed>| {
+            let request = TransferRequest::new(
+                &mut asset,
+                vec![outpoint(7)],
+                vec![outcoins("4", 3)],
+                vec![],
+            );
+            let request = match reveal {
+                Some(value) => request.reveal(node_id, 3, value),
+                None => request,
+            };
+            processor.transfer(request).map(|result| result.transition)
+        };
+        assert_eq!(
+            transfer(None).unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::MissingReveal {
+                outpoint: outpoint(7),
+                index: 3
+            })
+        );
+        let wrong = value::Revealed {
+            value: 5,
+            blinding: secret.blinding.clone(),
+        };
+        assert_eq!(
+            transfer(Some(wrong)).unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::InvalidReveal {
+                outpoint: outpoint(7),
+                index: 3
+            })
+        );
+        let transition = transfer(Some(secret)).unwrap();
+        assert_eq!(revealed_outputs(&transition), vec![(3, 4)]);
+    }
+
+    #[test]
+    fn test_transfer_witness_vout() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![
+                outcoins("3", 4),
+                Outcoins::with_witness_vout("1".parse().unwrap(), 1),
+            ],
+            vec![],
+            Some(SealSpec {
+                vout: 2,
+                txid: None,
+            }),
+        )
+        .unwrap();
+        assert_eq!(revealed_outputs(&transition), vec![(4, 3)]);
+
+        let mut witness_outputs = transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .unwrap()
+            .to_discrete_state()
+            .into_iter()
+            .filter_map(|state| match state {
+                OwnedState::Revealed {
+                    seal_definition: seal::Revealed::WitnessVout { vout, .. },
+                    assigned_state,
+                } => Some((vout, assigned_state.value)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        witness_outputs.sort();
+        assert_eq!(witness_outputs, vec![(1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_dust_allocations() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let err = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(1)],
+            vec![outcoins("3", 4), outcoins("0", 5)],
+            vec![],
+            None,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, ServiceErrorDomain::Schema(ref msg) if msg.contains("vout: 5"))
+        );
+
+        processor.set_dust_limit(Some(2));
+        let err = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(1)],
+            vec![outcoins("2", 4), outcoins("1", 5)],
+            vec![],
+            None,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, ServiceErrorDomain::Schema(ref msg) if msg.contains("vout: 5"))
+        );
+        transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(1)],
+            vec![outcoins("3", 4)],
+            vec![],
+            None,
+        )
+        .unwrap();
+
+        // Zero allocations are rejected with the dust check disabled
+        processor.set_dust_limit(None);
+        assert_eq!(processor.dust_limit(), None);
+        assert!(matches!(
+            processor.issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                    .allocation(outcoins("1", 0))
+                    .allocation(outcoins("0", 1))
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_inflate_updates_asset() {
+        let mut asset = issue_inflatable(0, 10);
+        let mut processor = processor();
+        processor
+            .inflate(
+                &mut asset,
+                outpoint(1),
+                vec![outcoins("4", 2)],
+                change_seal(3),
+                None,
+            )
+            .unwrap();
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 5);
+        assert_eq!(asset.total_supply(), 5);
+        assert_eq!(asset.known_issues().len(), 2);
+        assert!(asset.known_issues()[1].is_secondary());
+        assert_eq!(asset.balance(&[outpoint(2)]), 4);
+        assert!(asset
+            .right(&outpoint(1), OwnedRightsType::Inflation)
+            .is_none());
+        assert!(asset
+            .right(&outpoint(3), OwnedRightsType::Inflation)
+            .is_some());
+
+        // The spent right can't be reused, while the forwarded one is
+        // limited by the rest of the supply
+        assert_eq!(
+            processor
+                .inflate(
+                    &mut asset,
+                    outpoint(1),
+                    vec![outcoins("1", 4)],
+                    None,
+                    None
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Inflation(
+                InflationError::UnknownInflationRight(outpoint(1))
+            )
+        );
+        assert_eq!(
+            processor
+                .inflate(
+                    &mut asset,
+                    outpoint(3),
+                    vec![outcoins("6", 4)],
+                    None,
+                    None
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Inflation(InflationError::MaxSupplyExceeded {
+                requested: 6,
+                available: 5,
+            })
+        );
+        processor
+            .inflate(
+                &mut asset,
+                outpoint(3),
+                vec![outcoins("5", 4)],
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 10);
+    }
+
+    #[test]
+    fn test_validate_transition() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("3", 3)],
+            vec![],
+            change_seal(4),
+        )
+        .unwrap();
+        processor.validate_transition(&asset, &transition).unwrap();
+
+        let alloc = asset.allocations(&outpoint(0)).unwrap()[0].clone();
+        let mut parent = ParentOwnedRights::new();
+        parent.insert(
+            *alloc.node_id(),
+            bmap! { *OwnedRightsType::Assets => vec![*alloc.index()] },
+        );
+        let transfer = |right_type: usize, amount: AtomicValue| {
+            let mut owned_rights = BTreeMap::new();
+            owned_rights.insert(
+                right_type,
+                Assignments::zero_balanced(
+                    vec![alloc.value().clone()],
+                    vec![(change_seal(3).unwrap().seal_definition(), amount)],
+                    vec![],
+                ),
+            );
+            Transition::with(
+                *TransitionType::Transfer,
+                type_map! {}.into(),
+                parent.clone(),
+                owned_rights,
+                bset![],
+                vec![],
+            )
+        };
+
+        processor
+            .validate_transition(&asset, &transfer(*OwnedRightsType::Assets, 5))
+            .unwrap();
+        assert_eq!(
+            processor
+                .validate_transition(
+                    &asset,
+                    &transfer(*OwnedRightsType::Assets, 4)
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Validation(ValidationError::Unbalanced {
+                inputs: 5,
+                outputs: 4
+            })
+        );
+        assert_eq!(
+            processor
+                .validate_transition(&asset, &transfer(99, 5))
+                .unwrap_err(),
+            ServiceErrorDomain::Validation(
+                ValidationError::UnexpectedOwnedRights {
+                    transition_type: *TransitionType::Transfer,
+                    right_type: 99
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_split_allocation() {
+        use lnpbp::bp::blind::OutpointReveal;
+        use lnpbp::client_side_validation::Conceal;
+
+        let (mut asset, _) =
+            issue(IssueStructure::SingleIssue, vec![outcoins("100", 0)], 0)
+                .unwrap();
+        let mut processor = processor();
+        let spend = Outcoincealed {
+            coins: "30".parse().unwrap(),
+            seal_confidential: OutpointReveal {
+                blinding: 0x5a5a,
+                txid: txid(),
+                vout: 7,
+            }
+            .conceal(),
+        };
+        let transition = processor
+            .split_allocation(
+                &mut asset,
+                outpoint(0),
+                spend.clone(),
+                change_seal(4).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(revealed_outputs(&transition), vec![(4, 70)]);
+        let spent = transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .unwrap()
+            .to_discrete_state()
+            .into_iter()
+            .filter_map(|state| match state {
+                OwnedState::ConfidentialSeal { assigned_state, .. } => {
+                    Some(assigned_state.value)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(spent, vec![30]);
+        // Change commitment balances the input
+        processor.validate_transition(&asset, &transition).unwrap();
+
+        let overspend = Outcoincealed {
+            coins: "101".parse().unwrap(),
+            ..spend
+        };
+        assert_eq!(
+            processor
+                .split_allocation(
+                    &mut asset,
+                    outpoint(0),
+                    overspend,
+                    change_seal(4).unwrap(),
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::InsufficientFunds {
+                required: 101,
+                available: 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_issue_builder() {
+        let mut processor = processor();
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .precision(2)
+            .allocation(outcoins("1.5", 0));
+        assert_eq!(
+            builder.clone().spec().unwrap().issue_structure,
+            IssueStructure::SingleIssue
+        );
+        let (asset, _) = processor.issue(builder).unwrap();
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 150);
+
+        let spec = IssueBuilder::new(bp::Chain::Testnet3, "INFL", "Inflatable")
+            .allocation(outcoins("1", 0))
+            .max_supply(10)
+            .reissue_control(change_seal(1).unwrap())
+            .prune_seal(change_seal(2).unwrap())
+            .spec()
+            .unwrap();
+        assert_eq!(
+            spec.issue_structure,
+            IssueStructure::MultipleIssues {
+                max_supply: 10,
+                reissue_control: change_seal(1).unwrap(),
+                inflation_not_before: None,
+            }
+        );
+        assert_eq!(spec.prune_seals, vec![change_seal(2).unwrap()]);
+
+        assert!(matches!(
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .reissue_control(change_seal(1).unwrap())
+                .spec(),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert!(matches!(
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .max_supply(10)
+                .spec(),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_transfer_chain_mismatch() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        processor.set_chain(bp::Chain::Mainnet);
+        assert!(matches!(
+            transfer(
+                &mut processor,
+                &mut asset,
+                vec![outpoint(0)],
+                vec![outcoins("5", 3)],
+                vec![],
+                None
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+
+        processor.set_chain(bp::Chain::Testnet3);
+        transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("5", 3)],
+            vec![],
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_transfer_chain_blinded_output() {
+        use lnpbp::bp::blind::OutpointReveal;
+        use lnpbp::client_side_validation::Conceal;
+
+        let mut asset = issue_spread();
+        let theirs = vec![Outcoincealed {
+            coins: "5".parse().unwrap(),
+            seal_confidential: OutpointReveal {
+                blinding: 0x5a5a,
+                txid: txid(),
+                vout: 7,
+            }
+            .conceal(),
+        }];
+        // Testnet asset can't be sent by the processor expecting mainnet
+        // seals, whatever the seals are
+        let mut processor = processor();
+        processor.set_chain(bp::Chain::Mainnet);
+        assert!(matches!(
+            transfer(
+                &mut processor,
+                &mut asset,
+                vec![outpoint(0)],
+                vec![],
+                theirs.clone(),
+                None
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        // Network-agnostic blinded seals pass on the matching network
+        processor.set_chain(bp::Chain::Testnet3);
+        transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![],
+            theirs,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_issue_canonical_order() {
+        // Blinding factors and timestamp are fixed, so the genesis depends
+        // only on the allocations
+        let issue_ordered = |allocations: Vec<Outcoins>| {
+            let builder = allocations.into_iter().fold(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                    .timestamp(1602340666)
+                    .blinding_seed([0x2a; 32]),
+                IssueBuilder::allocation,
+            );
+            let (_, genesis) = processor().issue(builder).unwrap();
+            genesis
+        };
+        let genesis_outputs = |genesis: &Genesis| {
+            genesis
+                .owned_rights_by_type(*OwnedRightsType::Assets)
+                .unwrap()
+                .to_discrete_state()
+                .into_iter()
+                .filter_map(|state| match state {
+                    OwnedState::Revealed {
+                        seal_definition: seal::Revealed::TxOutpoint(reveal),
+                        assigned_state,
+                    } => Some((reveal.vout, assigned_state.value)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let genesis = issue_ordered(vec![
+            outcoins("2", 1),
+            outcoins("5", 0),
+            outcoins("3", 0),
+        ]);
+        assert_eq!(genesis_outputs(&genesis), vec![(0, 3), (0, 5), (1, 2)]);
+        for permutation in vec![
+            vec![outcoins("3", 0), outcoins("2", 1), outcoins("5", 0)],
+            vec![outcoins("5", 0), outcoins("3", 0), outcoins("2", 1)],
+        ] {
+            let permuted = issue_ordered(permutation);
+            assert_eq!(genesis_outputs(&permuted), genesis_outputs(&genesis));
+            assert_eq!(permuted.contract_id(), genesis.contract_id());
+        }
+    }
+
+    #[test]
+    fn test_issuance_receipt() {
+        let mut processor = processor_v2();
+        let (asset, genesis) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "INFL", "Inflatable")
+                    .precision(2)
+                    .allocation(outcoins("1", 0))
+                    .allocation(outcoins("0.5", 0))
+                    .max_supply(1000)
+                    .reissue_control(change_seal(1).unwrap())
+                    .prune_seal(change_seal(2).unwrap()),
+            )
+            .unwrap();
+        let receipt = asset.issuance_receipt();
+        assert_eq!(*receipt.contract_id(), genesis.contract_id());
+        assert_eq!(*receipt.schema_id(), genesis.schema_id());
+        assert_eq!(*receipt.allocations(), vec![(outpoint(0), 150)]);
+        assert!(*receipt.is_inflatable());
+        assert_eq!(*receipt.reissue_controls(), vec![outpoint(1)]);
+        assert_eq!(*receipt.burn_controls(), vec![outpoint(2)]);
+        assert_eq!(
+            genesis.metadata().u64(*FieldType::IssuedSupply),
+            vec![*receipt.issued_supply()]
+        );
+        assert_eq!(receipt.issued_supply_decimal().to_string(), "1.50");
+        assert_eq!(*receipt.max_supply(), 1000);
+        assert_eq!(receipt.max_supply_decimal().to_string(), "10.00");
+    }
+
+    #[test]
+    fn test_issue_extra_fields() {
+        let mut processor = processor_v2();
+        let commitment = vec![0x5a; 32];
+        let (asset, genesis) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                    .allocation(outcoins("1", 0))
+                    .field(
+                        FieldType::ContactUri,
+                        data::Revealed::String(s!("mailto:issuer@example.com")),
+                    )
+                    .field(
+                        FieldType::IssuerCommitment,
+                        data::Revealed::Bytes(commitment.clone()),
+                    ),
+            )
+            .unwrap();
+        assert_eq!(
+            genesis.metadata().string(*FieldType::ContactUri),
+            vec![s!("mailto:issuer@example.com")]
+        );
+        assert_eq!(
+            asset.field(FieldType::ContactUri),
+            Some(&data::Revealed::String(s!("mailto:issuer@example.com")))
+        );
+        assert_eq!(
+            asset.field(FieldType::IssuerCommitment),
+            Some(&data::Revealed::Bytes(commitment))
+        );
+
+        // Fields derived from the issue arguments can't be overridden
+        assert!(matches!(
+            processor.issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                    .allocation(outcoins("1", 0))
+                    .field(FieldType::IssuedSupply, data::Revealed::U64(100)),
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_schema_v1() {
+        let builders = vec![
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("1", 0))
+                .field(
+                    FieldType::ContactUri,
+                    data::Revealed::String(s!("mailto:issuer@example.com")),
+                ),
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("1", 0))
+                .attachment(b"Asset prospectus".to_vec()),
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("1", 0))
+                .prune_seal(change_seal(2).unwrap()),
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("1", 0))
+                .max_supply(10)
+                .reissue_control(change_seal(1).unwrap())
+                .inflation_not_before(700_000),
+        ];
+        // Genesis of the first schema version can't carry the fields and
+        // rights added by the second one
+        let mut processor = processor();
+        for builder in builders {
+            assert!(processor_v2().validate_issue(&builder).is_ok());
+            assert!(matches!(
+                processor.issue(builder),
+                Err(ServiceErrorDomain::Schema(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_transfer_multi() {
+        let mut first = issue_spread();
+        let (mut second, _) = issue(
+            IssueStructure::SingleIssue,
+            vec![outcoins("7", 0), outcoins("4", 6)],
+            0,
+        )
+        .unwrap();
+        let mut processor = processor();
+
+        let transitions = processor
+            .transfer_multi(vec![
+                TransferRequest::new(
+                    &mut first,
+                    vec![outpoint(0)],
+                    vec![outcoins("5", 3)],
+                    vec![],
+                ),
+                TransferRequest::new(
+                    &mut second,
+                    vec![outpoint(0)],
+                    vec![outcoins("7", 3)],
+                    vec![],
+                ),
+            ])
+            .unwrap();
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(revealed_outputs(&transitions[0]), vec![(3, 5)]);
+        assert_eq!(revealed_outputs(&transitions[1]), vec![(3, 7)]);
+        // Both assets record the spent and the new allocations
+        assert_eq!(first.balance(&[outpoint(0)]), 0);
+        assert_eq!(first.balance(&[outpoint(3)]), 5);
+        assert_eq!(second.balance(&[outpoint(0)]), 0);
+        assert_eq!(second.balance(&[outpoint(3)]), 7);
+        assert_eq!(second.balance(&[outpoint(6)]), 4);
+
+        // Spending the new outpoint of the first asset would destroy the
+        // allocation of the second asset on it
+        assert_eq!(
+            processor
+                .transfer_multi(vec![
+                    TransferRequest::new(
+                        &mut first,
+                        vec![outpoint(3)],
+                        vec![outcoins("5", 4)],
+                        vec![]
+                    ),
+                    TransferRequest::new(
+                        &mut second,
+                        vec![outpoint(6)],
+                        vec![outcoins("4", 4)],
+                        vec![]
+                    ),
+                ])
+                .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::UnspentSharedSeal(
+                outpoint(3)
+            ))
+        );
+        // Failed batch does not change the assets
+        assert_eq!(first.balance(&[outpoint(3)]), 5);
+        assert_eq!(second.balance(&[outpoint(3), outpoint(6)]), 11);
+    }
+
+    #[test]
+    fn test_transfer_multi_mixed_networks() {
+        let mut testnet = issue_spread();
+        let (mut mainnet, _) = processor()
+            .issue(
+                IssueBuilder::new(bp::Chain::Mainnet, "MAIN", "Mainnet asset")
+                    .allocation(outcoins("4", 6)),
+            )
+            .unwrap();
+        assert!(matches!(
+            processor().transfer_multi(vec![
+                TransferRequest::new(
+                    &mut testnet,
+                    vec![outpoint(0)],
+                    vec![outcoins("5", 3)],
+                    vec![]
+                ),
+                TransferRequest::new(
+                    &mut mainnet,
+                    vec![outpoint(6)],
+                    vec![outcoins("4", 3)],
+                    vec![]
+                ),
+            ]),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_coin_selection() {
+        use CoinSelection::*;
+
+        let mut asset = issue_spread();
+        let processor = processor();
+        let select = |target, selection: CoinSelection| {
+            processor.select_coins(&asset, target, &selection).unwrap()
+        };
+        assert_eq!(select(5, LargestFirst), vec![outpoint(0)]);
+        assert_eq!(select(5, SmallestFirst), vec![outpoint(2), outpoint(1)]);
+        assert_eq!(select(5, BranchAndBound), vec![outpoint(0)]);
+        assert_eq!(select(7, LargestFirst), vec![outpoint(0), outpoint(1)]);
+        assert_eq!(select(7, BranchAndBound), vec![outpoint(0), outpoint(2)]);
+        // No exact match, so falls back to the largest-first selection
+        assert_eq!(select(6, BranchAndBound), vec![outpoint(0), outpoint(1)]);
+        assert_eq!(
+            select(5, Manual(vec![outpoint(1), outpoint(2)])),
+            vec![outpoint(1), outpoint(2)]
+        );
+        assert_eq!(
+            processor
+                .select_coins(&asset, 5, &Manual(vec![outpoint(1)]))
+                .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::InsufficientFunds {
+                required: 5,
+                available: 3
+            })
+        );
+
+        let mut processor = processor;
+        let result = processor
+            .transfer(
+                request(
+                    &mut asset,
+                    vec![],
+                    vec![outcoins("4", 3)],
+                    vec![],
+                    change_seal(4),
+                )
+                .coin_selection(SmallestFirst),
+            )
+            .unwrap();
+        assert_eq!(result.inputs, vec![outpoint(2), outpoint(1)]);
+        let mut outputs = revealed_outputs(&result.transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(3, 4), (4, 1)]);
+    }
+
+    #[test]
+    fn test_transfer_spent() {
+        let (mut asset, genesis) = issue(
+            IssueStructure::SingleIssue,
+            vec![outcoins("5", 0), outcoins("3", 1), outcoins("2", 2)],
+            0,
+        )
+        .unwrap();
+        let mut processor = processor();
+        let result = processor
+            .transfer(TransferRequest::new(
+                &mut asset,
+                vec![outpoint(2), outpoint(0)],
+                vec![outcoins("7", 3)],
+                vec![],
+            ))
+            .unwrap();
+        let node_id = genesis.node_id();
+        // Allocations are sorted by outpoint at issue, so assignment indexes
+        // match the vouts
+        assert_eq!(
+            result.spent,
+            vec![
+                (node_id, OwnedRightsType::Assets, 2),
+                (node_id, OwnedRightsType::Assets, 0)
+            ]
+        );
+        assert_eq!(
+            result.transition.parent_owned_rights().get(&node_id),
+            Some(&bmap! { *OwnedRightsType::Assets => vec![2, 0] })
+        );
+
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(2), outpoint(0)],
+            vec![outcoins("7", 3)],
+            vec![],
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            transition.parent_owned_rights(),
+            result.transition.parent_owned_rights()
+        );
+    }
+
+    #[test]
+    fn test_transfer_duplicate_input() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let err = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0), outpoint(1), outpoint(0)],
+            vec![outcoins("10", 3)],
+            vec![],
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Transfer(TransferError::DuplicateInput(
+                outpoint(0)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_transfer_manual_duplicate_input() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        // Balance of the manual selection counts each outpoint once, so the
+        // duplicate passes the funds check but is rejected by the transfer
+        let err = processor
+            .transfer(
+                TransferRequest::new(
+                    &mut asset,
+                    vec![],
+                    vec![outcoins("5", 3)],
+                    vec![],
+                )
+                .coin_selection(CoinSelection::Manual(vec![
+                    outpoint(0),
+                    outpoint(0),
+                ])),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Transfer(TransferError::DuplicateInput(
+                outpoint(0)
+            ))
+        );
+        assert_eq!(asset.balance(&[outpoint(0)]), 5);
+    }
+
+    #[test]
+    fn test_transfer_blindings() {
+        use lnpbp::bp::blind::OutpointReveal;
+        use lnpbp::client_side_validation::Conceal;
+
+        let mut asset = issue_spread();
+        let seal_confidential = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 7,
+        }
+        .conceal();
+        let theirs = vec![Outcoincealed {
+            coins: "4".parse().unwrap(),
+            seal_confidential,
+        }];
+        let mut processor = processor();
+        let result = processor
+            .transfer(TransferRequest::new(
+                &mut asset,
+                vec![outpoint(0)],
+                vec![outcoins("1", 3)],
+                theirs,
+            ))
+            .unwrap();
+        assert_eq!(result.blindings.len(), 1);
+        let blinding = result.blindings[&seal_confidential];
+
+        // The returned blinding factor opens the produced commitment
+        let commitments = result
+            .transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .unwrap()
+            .to_discrete_state()
+            .into_iter()
+            .filter_map(|state| match state {
+                OwnedState::ConfidentialSeal {
+                    seal_definition,
+                    assigned_state,
+                } if seal_definition == seal_confidential => {
+                    Some(assigned_state.conceal())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let opening = value::Revealed { value: 4, blinding }.conceal();
+        assert_eq!(commitments, vec![opening]);
+        assert_ne!(value::Revealed { value: 5, blinding }.conceal(), opening);
+    }
+
+    #[test]
+    fn test_validate_transfer() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let plan = processor
+            .validate_transfer(&request(
+                &mut asset,
+                vec![outpoint(0), outpoint(2)],
+                vec![outcoins("4", 3)],
+                vec![],
+                change_seal(4),
+            ))
+            .unwrap();
+        assert_eq!(plan.total_inputs(), 7);
+        assert_eq!(plan.total_outputs(), 4);
+        assert_eq!(
+            *plan.change(),
+            Some((change_seal(4).unwrap().seal_definition(), 3))
+        );
+        let mut outputs =
+            revealed_outputs(&processor.transfer_planned(plan).transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(3, 4), (4, 3)]);
+
+        // Invalid transfers fail the validation as they fail the transfer
+        let invalid = vec![
+            (vec![outpoint(0)], vec![outcoins("6", 3)], None),
+            (vec![outpoint(0)], vec![outcoins("4", 3)], None),
+            (vec![outpoint(9)], vec![outcoins("4", 3)], change_seal(4)),
+            (vec![outpoint(0), outpoint(0)], vec![outcoins("4", 3)], None),
+            (vec![outpoint(0)], vec![outcoins("0", 3)], change_seal(4)),
+        ];
+        for (inputs, ours, change) in invalid {
+            let err = processor
+                .validate_transfer(&request(
+                    &mut asset,
+                    inputs.clone(),
+                    ours.clone(),
+                    vec![],
+                    change.clone(),
+                ))
+                .unwrap_err();
+            assert_eq!(
+                transfer(
+                    &mut processor,
+                    &mut asset,
+                    inputs,
+                    ours,
+                    vec![],
+                    change
+                )
+                .unwrap_err(),
+                err
+            );
+        }
+    }
+
+    fn issue_renominable() -> Asset {
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("5", 0))
+            .renomination_seal(change_seal(5).unwrap());
+        let (asset, _) = processor().issue(builder).unwrap();
+        asset
+    }
+
+    #[test]
+    fn test_issue_renomination_seal() {
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("5", 0))
+            .renomination_seal(change_seal(5).unwrap());
+        let (mut asset, genesis) = processor().issue(builder).unwrap();
+        assert_eq!(
+            genesis
+                .owned_rights_by_type(*OwnedRightsType::Renomination)
+                .map(|assignments| assignments.to_declarative_state().len()),
+            Some(1)
+        );
+        let right = asset
+            .right(&outpoint(5), OwnedRightsType::Renomination)
+            .cloned()
+            .unwrap();
+        assert_eq!(*right.node_id(), genesis.node_id());
+
+        let transition = processor()
+            .renominate(
+                &mut asset,
+                outpoint(5),
+                Some(s!("TKR")),
+                None,
+                change_seal(6).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(transition.transition_type(), *TransitionType::Renomination);
+        assert_eq!(asset.ticker(), "TKR");
+
+        // Without renomination seal no renomination right is assigned
+        let (asset, genesis) =
+            issue(IssueStructure::SingleIssue, vec![outcoins("5", 0)], 0)
+                .unwrap();
+        assert!(genesis
+            .owned_rights_by_type(*OwnedRightsType::Renomination)
+            .is_none());
+        assert!(asset.rights(&outpoint(5)).is_none());
+    }
+
+    #[test]
+    fn test_renominate_name() {
+        let mut asset = issue_renominable();
+        let mut processor = processor();
+        let transition = processor
+            .renominate(
+                &mut asset,
+                outpoint(5),
+                None,
+                Some(s!("Renamed asset")),
+                change_seal(6).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(transition.transition_type(), *TransitionType::Renomination);
+        assert_eq!(
+            transition.metadata().string(*FieldType::Name),
+            vec![s!("Renamed asset")]
+        );
+        assert!(transition.metadata().string(*FieldType::Ticker).is_empty());
+        assert_eq!(asset.name(), "Renamed asset");
+        assert_eq!(asset.ticker(), "TICK");
+
+        assert!(matches!(
+            processor.renominate(
+                &mut asset,
+                outpoint(6),
+                None,
+                None,
+                change_seal(7).unwrap()
+            ),
+            Err(ServiceErrorDomain::Renomination(
+                RenominationError::NothingToRenominate
+            ))
+        ));
+        // New nomination is validated with the issue rules
+        assert!(matches!(
+            processor.renominate(
+                &mut asset,
+                outpoint(6),
+                Some(s!("tick")),
+                None,
+                change_seal(7).unwrap()
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert_eq!(asset.ticker(), "TICK");
+    }
+
+    #[test]
+    fn test_renominate_control_forward() {
+        let mut asset = issue_renominable();
+        let mut processor = processor();
+        let transition = processor
+            .renominate(
+                &mut asset,
+                outpoint(5),
+                Some(s!("NEW")),
+                None,
+                change_seal(6).unwrap(),
+            )
+            .unwrap();
+        assert!(asset
+            .right(&outpoint(5), OwnedRightsType::Renomination)
+            .is_none());
+        assert_eq!(
+            *asset
+                .right(&outpoint(6), OwnedRightsType::Renomination)
+                .unwrap()
+                .node_id(),
+            transition.node_id()
+        );
+
+        // The spent right can't be used again, while the forwarded one can
+        assert_eq!(
+            processor
+                .renominate(
+                    &mut asset,
+                    outpoint(5),
+                    Some(s!("OLD")),
+                    None,
+                    change_seal(7).unwrap()
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Renomination(
+                RenominationError::UnknownRenominationRight(outpoint(5))
+            )
+        );
+        let next = processor
+            .renominate(
+                &mut asset,
+                outpoint(6),
+                Some(s!("NEWER")),
+                None,
+                change_seal(7).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            next.parent_owned_rights().get(&transition.node_id()),
+            Some(&bmap! { *OwnedRightsType::Renomination => vec![0] })
+        );
+        assert_eq!(asset.ticker(), "NEWER");
+    }
+
+    #[test]
+    fn test_update_contract_text() {
+        let mut asset = issue_renominable();
+        let mut processor = processor();
+        processor.set_description_limit(16);
+        let transition = processor
+            .update_contract_text(
+                &mut asset,
+                outpoint(5),
+                s!("Updated terms"),
+                change_seal(6).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            transition.metadata().string(*FieldType::ContractText),
+            vec![s!("Updated terms")]
+        );
+        assert!(transition.metadata().string(*FieldType::Name).is_empty());
+        assert_eq!(asset.description().as_deref(), Some("Updated terms"));
+        assert!(asset
+            .right(&outpoint(6), OwnedRightsType::Renomination)
+            .is_some());
+
+        assert!(matches!(
+            processor.update_contract_text(
+                &mut asset,
+                outpoint(6),
+                s!("Terms exceeding the limit"),
+                change_seal(7).unwrap()
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert_eq!(asset.description().as_deref(), Some("Updated terms"));
+    }
+
+    #[test]
+    fn test_issue_custom_blinding() {
+        use lnpbp::client_side_validation::Conceal;
+
+        let builder = || {
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                .allocation(outcoins("10", 0))
+        };
+        let mut blinding = [0u8; 32];
+        blinding[31] = 0x2a;
+        let key = secp256k1zkp::key::SecretKey::from_slice(
+            &secp256k1zkp::Secp256k1::new(),
+            &blinding,
+        )
+        .unwrap();
+
+        let mut processor = processor();
+        let (asset, _) = processor.issue(builder().blinding(blinding)).unwrap();
+        // With a single allocation it takes the issued supply blinding
+        let allocation = asset.allocations(&outpoint(0)).unwrap()[0].clone();
+        assert_eq!(allocation.value().blinding, key);
+        let default = value::Revealed {
+            value: 10,
+            blinding: secp256k1zkp::key::ONE_KEY,
+        };
+        assert_ne!(allocation.value().clone().conceal(), default.conceal());
+
+        for invalid in &[[0u8; 32], [0xffu8; 32]] {
+            assert!(matches!(
+                builder().blinding(*invalid).spec(),
+                Err(ServiceErrorDomain::Schema(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_transfer_multi_underfunded() {
+        let mut first = issue_spread();
+        let (mut second, _) =
+            issue(IssueStructure::SingleIssue, vec![outcoins("4", 6)], 0)
+                .unwrap();
+        let result = processor().transfer_multi(vec![
+            TransferRequest::new(
+                &mut first,
+                vec![outpoint(0)],
+                vec![outcoins("5", 3)],
+                vec![],
+            ),
+            TransferRequest::new(
+                &mut second,
+                vec![outpoint(6)],
+                vec![outcoins("5", 3)],
+                vec![],
+            ),
+        ]);
+        assert_eq!(
+            result,
+            Err(ServiceErrorDomain::Transfer(
+                TransferError::AmountMismatch {
+                    available: 4,
+                    requested: 5,
+                    precision: 0,
+                }
+            ))
+        );
+        // Neither asset is changed by the failed batch
+        assert_eq!(first.balance(&[outpoint(0)]), 5);
+        assert_eq!(second.balance(&[outpoint(6)]), 4);
+    }
+
+    #[test]
+    fn test_consolidate_tx() {
+        let (mut asset, genesis) = issue(
+            IssueStructure::SingleIssue,
+            vec![outcoins("2", 0), outcoins("3", 0), outcoins("4", 0)],
+            0,
+        )
+        .unwrap();
+        assert_eq!(asset.allocations(&outpoint(0)).unwrap().len(), 3);
+        assert_eq!(asset.consolidate(), bmap! { outpoint(0) => 9u64 });
+
+        let transition = processor()
+            .consolidate_tx(&mut asset, outpoint(0), change_seal(5).unwrap())
+            .unwrap();
+        assert_eq!(revealed_outputs(&transition), vec![(5, 9)]);
+        assert_eq!(
+            transition.parent_owned_rights().get(&genesis.node_id()),
+            Some(&bmap! { *OwnedRightsType::Assets => vec![0, 1, 2] })
+        );
+    }
+
+    #[test]
+    fn test_transfer_plan_seals() {
+        use lnpbp::bp::blind::OutpointReveal;
+
+        let mut asset = issue_spread();
+        let seal_confidential = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 7,
+        }
+        .conceal();
+        let ours = vec![
+            outcoins("2", 3),
+            Outcoins::with_witness_vout("1".parse().unwrap(), 4),
+        ];
+        let inputs = vec![outpoint(2), outpoint(0)];
+        let plan = processor()
+            .validate_transfer(&request(
+                &mut asset,
+                inputs.clone(),
+                ours.clone(),
+                vec![Outcoincealed {
+                    coins: "3".parse().unwrap(),
+                    seal_confidential,
+                }],
+                change_seal(5),
+            ))
+            .unwrap();
+
+        assert_eq!(plan.consumed(), inputs.into_iter().collect());
+        let mut produced = ours
+            .iter()
+            .map(|outcoins| outcoins.seal_definition().conceal())
+            .collect::<Vec<_>>();
+        produced.push(seal_confidential);
+        assert_eq!(plan.produced(), produced);
+        assert_eq!(
+            plan.change_seal(),
+            Some(&change_seal(5).unwrap().seal_definition())
+        );
+    }
+
+    #[test]
+    fn test_issue_ids() {
+        let mut processor = processor();
+        let (asset, genesis) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                    .allocation(outcoins("10", 0)),
+            )
+            .unwrap();
+        assert_eq!(genesis.schema_id(), schema::schema().schema_id());
+        assert_eq!(processor.schema_id(), genesis.schema_id());
+        assert_eq!(asset.contract_id(), genesis.contract_id());
+        assert_eq!(*asset.id(), genesis.contract_id());
+        // Stored asset is looked up by the genesis-derived contract id
+        assert_eq!(
+            processor
+                .asset_storage
+                .lock()
+                .unwrap()
+                .asset(genesis.contract_id())
+                .unwrap(),
+            asset
+        );
+    }
+
+    #[test]
+    fn test_issue_precision_bounds() {
+        let (asset, _) = issue(
+            IssueStructure::SingleIssue,
+            vec![outcoins("1", 0)],
+            MAX_PRECISION,
+        )
+        .unwrap();
+        assert_eq!(*asset.fractional_bits(), 18);
+        assert_eq!(asset.total_balance(), 1_000_000_000_000_000_000);
+
+        assert!(matches!(
+            issue(IssueStructure::SingleIssue, vec![outcoins("1", 0)], 19),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_precision_schema_max() {
+        use lnpbp::rgb::schema::{Bits, DataFormat};
+
+        assert_eq!(processor().max_precision(), MAX_PRECISION);
+
+        let mut custom_schema = schema::schema();
+        custom_schema.field_types.insert(
+            *FieldType::Precision,
+            DataFormat::Unsigned(Bits::Bit8, 0, 8),
+        );
+        let custom_processor = Processor::with_schema(
+            Arc::new(Mutex::new(MemoryCache::new())),
+            Arc::new(Mutex::new(MemoryCache::new())),
+            custom_schema,
+        )
+        .unwrap();
+        assert_eq!(custom_processor.max_precision(), 8);
+        let builder = |precision| {
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .precision(precision)
+                .allocation(outcoins("1", 0))
+        };
+        assert!(custom_processor.validate_issue(&builder(8)).is_ok());
+        match custom_processor.validate_issue(&builder(9)) {
+            Err(ServiceErrorDomain::Schema(msg)) => {
+                assert!(msg.contains("0..=8"))
+            }
+            _ => panic!("precision above the schema maximum is accepted"),
+        }
+    }
+
+    #[test]
+    fn test_import_asset() {
+        let (asset, genesis) = issue(
+            IssueStructure::SingleIssue,
+            vec![outcoins("5", 0), outcoins("3", 1)],
+            0,
+        )
+        .unwrap();
+
+        let mut processor = processor();
+        assert_eq!(processor.import_asset(genesis.clone()).unwrap(), asset);
+        assert!(matches!(
+            processor.import_asset(genesis.clone()),
+            Err(ServiceErrorDomain::Storage(_))
+        ));
+
+        let mismatched = Genesis::with(
+            schema::schema_v2().schema_id(),
+            bp::Chain::Testnet3,
+            genesis.metadata().clone(),
+            genesis.owned_rights().clone(),
+            bset![],
+            vec![],
+        );
+        assert!(matches!(
+            processor.import_asset(mismatched),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_structure_strict_encoding() {
+        use lnpbp::strict_encoding::strict_encode;
+
+        for issue_structure in &[
+            IssueStructure::SingleIssue,
+            IssueStructure::MultipleIssues {
+                max_supply: 1000,
+                reissue_control: change_seal(3).unwrap(),
+                inflation_not_before: None,
+            },
+            IssueStructure::MultipleIssues {
+                max_supply: 1000,
+                reissue_control: change_seal(3).unwrap(),
+                inflation_not_before: Some(700_000),
+            },
+        ] {
+            let data = strict_encode(issue_structure).unwrap();
+            assert_eq!(
+                IssueStructure::strict_decode(&data[..]).unwrap(),
+                *issue_structure
+            );
+        }
+        assert!(IssueStructure::strict_decode(&[2u8][..]).is_err());
+    }
+
+    #[test]
+    fn test_transfer_mixed_recipients() {
+        use lnpbp::bp::blind::OutpointReveal;
+
+        let mut asset = issue_spread();
+        let seal_confidential = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 7,
+        }
+        .conceal();
+        let mut processor = processor();
+        let result = processor
+            .transfer(request(
+                &mut asset,
+                vec![outpoint(0), outpoint(1), outpoint(2)],
+                // Revealed allocation of the auditable recipient
+                vec![outcoins("6", 8)],
+                vec![Outcoincealed {
+                    coins: "2".parse().unwrap(),
+                    seal_confidential,
+                }],
+                change_seal(4),
+            ))
+            .unwrap();
+        let mut outputs = revealed_outputs(&result.transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(4, 2), (8, 6)]);
+        assert_eq!(result.blindings.len(), 1);
+        processor
+            .validate_transition(&asset, &result.transition)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_transfer_with_fee() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let fee_seal = change_seal(7).unwrap();
+        let fee = "1".parse().unwrap();
+
+        // Inputs must cover the fee on top of the outputs
+        assert_eq!(
+            processor
+                .transfer(
+                    TransferRequest::new(
+                        &mut asset,
+                        vec![outpoint(0)],
+                        vec![outcoins("5", 3)],
+                        vec![],
+                    )
+                    .fee(fee_seal, fee),
+                )
+                .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::AmountMismatch {
+                available: 5,
+                requested: 6,
+                precision: 0,
+            })
+        );
+
+        let plan = processor
+            .validate_transfer(
+                &TransferRequest::new(
+                    &mut asset,
+                    vec![outpoint(0)],
+                    vec![outcoins("4", 3)],
+                    vec![],
+                )
+                .fee(fee_seal, fee),
+            )
+            .unwrap();
+        assert_eq!(plan.total_inputs(), plan.total_outputs());
+        assert_eq!(*plan.change(), None);
+        assert_eq!(plan.produced().len(), 2);
+
+        let transition = processor
+            .transfer(
+                TransferRequest::new(
+                    &mut asset,
+                    vec![outpoint(0)],
+                    vec![outcoins("4", 3)],
+                    vec![],
+                )
+                .fee(fee_seal, fee),
+            )
+            .unwrap()
+            .transition;
+        let mut outputs = revealed_outputs(&transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(3, 4), (7, 1)]);
+    }
+
+    #[test]
+    fn test_apply_transition() {
+        use lnpbp::bp::blind::OutpointReveal;
+
+        let mut asset = issue_spread();
+        let theirs = Outcoincealed {
+            coins: "3".parse().unwrap(),
+            seal_confidential: OutpointReveal {
+                blinding: 0x5a5a,
+                txid: txid(),
+                vout: 7,
+            }
+            .conceal(),
+        };
+        let mut processor = processor();
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![],
+            vec![theirs],
+            change_seal(4),
+        )
+        .unwrap();
+        assert_eq!(asset.total_balance(), 10);
+
+        asset.apply_transition(&transition);
+        assert_eq!(asset.total_balance(), 10 - 3);
+        assert_eq!(asset.balance(&[outpoint(0)]), 0);
+        assert_eq!(asset.balance(&[outpoint(4)]), 2);
+        // Applying the same transition twice does not change the state
+        asset.apply_transition(&transition);
+        assert_eq!(asset.total_balance(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_async_issue_transfer() {
+        let storage = Arc::new(Mutex::new(MemoryCache::new()));
+        let tasks = (0..4)
+            .map(|_| {
+                let storage = storage.clone();
+                tokio::spawn(async move {
+                    let mut processor = Processor::new(
+                        Arc::new(Mutex::new(MemoryCache::new())),
+                        storage,
+                    )
+                    .unwrap();
+                    let builder = IssueBuilder::new(
+                        bp::Chain::Testnet3,
+                        "TICK",
+                        "Ticker",
+                    )
+                    .allocation(outcoins("5", 0));
+                    let (mut asset, _) =
+                        processor.issue_async(builder).await.unwrap();
+                    // Transfer is storage-free, so it is called directly
+                    let transition = transfer(
+                        &mut processor,
+                        &mut asset,
+                        vec![outpoint(0)],
+                        vec![outcoins("4", 3)],
+                        vec![],
+                        change_seal(4),
+                    )
+                    .unwrap();
+                    (asset, transition)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for task in futures::future::join_all(tasks).await {
+            let (asset, transition) = task.unwrap();
+            let mut outputs = revealed_outputs(&transition);
+            outputs.sort();
+            assert_eq!(outputs, vec![(3, 4), (4, 1)]);
+            assert_eq!(
+                storage.lock().unwrap().asset(*asset.id()).unwrap(),
+                asset
+            );
+        }
+        assert_eq!(storage.lock().unwrap().assets().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_issue_colliding_right_seals() {
+        let mut processor = processor_v2();
+        let seal = change_seal(1).unwrap();
+        let builders = vec![
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .max_supply(10)
+                .reissue_control(seal)
+                .prune_seal(seal),
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("1", 0))
+                .prune_seal(seal)
+                .prune_seal(seal),
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("1", 0))
+                .prune_seal(seal)
+                .renomination_seal(seal),
+        ];
+        for builder in builders {
+            assert!(matches!(
+                processor.issue(builder),
+                Err(ServiceErrorDomain::Schema(_))
+            ));
+        }
+
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .max_supply(10)
+            .reissue_control(seal)
+            .prune_seal(change_seal(2).unwrap());
+        assert!(processor.issue(builder).is_ok());
+    }
+
+    #[test]
+    fn test_prune_spent() {
+        let mut asset = issue_spread();
+        let genesis_id = *asset.allocations(&outpoint(0)).unwrap()[0].node_id();
+        let mut processor = processor();
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("4", 3)],
+            vec![],
+            change_seal(4),
+        )
+        .unwrap();
+
+        // Pruning unknown seals does not affect the allocations
+        assert_eq!(asset.prune_spent(&[outpoint(9)]), 0);
+        assert_eq!(asset.prune_spent(&[outpoint(0), outpoint(0)]), 1);
+        assert_eq!(
+            asset.consolidate(),
+            bmap! { outpoint(1) => 3, outpoint(2) => 2 }
+        );
+
+        asset.apply_transition(&transition);
+        assert_eq!(asset.prune_nodes(&bset! { genesis_id }), 2);
+        assert_eq!(
+            asset.consolidate(),
+            bmap! { outpoint(3) => 4, outpoint(4) => 1 }
+        );
+        assert_eq!(asset.prune_nodes(&bset! { genesis_id }), 0);
+    }
+
+    #[test]
+    fn test_issue_reissue_shares() {
+        let mut processor = processor();
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("2", 0))
+            .max_supply(12)
+            .reissue_control(change_seal(1).unwrap())
+            .reissue_share(change_seal(2).unwrap(), 5);
+        let (asset, genesis) = processor.issue(builder).unwrap();
+        assert_eq!(
+            genesis
+                .owned_rights_by_type(*OwnedRightsType::Inflation)
+                .map(|assignments| assignments.to_custom_state().len()),
+            Some(2)
+        );
+        let mut inflation = asset
+            .known_inflation()
+            .iter()
+            .map(|(outpoint, amount)| (outpoint.vout, amount.atomic_value()))
+            .collect::<Vec<_>>();
+        inflation.sort();
+        assert_eq!(inflation, vec![(1, 5), (2, 5)]);
+
+        // Shares can't exceed the inflation allowance
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("2", 0))
+            .max_supply(12)
+            .reissue_control(change_seal(1).unwrap())
+            .reissue_share(change_seal(2).unwrap(), 5)
+            .reissue_share(change_seal(3).unwrap(), 6);
+        assert!(matches!(
+            processor.issue(builder),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        // Shares require inflatable asset
+        assert!(matches!(
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("2", 0))
+                .reissue_share(change_seal(2).unwrap(), 5)
+                .spec(),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_attachment() {
+        use lnpbp::hashes::{sha256, Hash};
+
+        let mut processor = processor_v2();
+        let document = b"Asset prospectus".to_vec();
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("1", 0))
+            .attachment(document.clone());
+        let (asset, genesis) = processor.issue(builder).unwrap();
+        assert_eq!(
+            genesis.metadata().bytes(*FieldType::Attachment),
+            vec![document.clone()]
+        );
+        assert_eq!(
+            sha256::Hash::hash(asset.attachment().unwrap()),
+            sha256::Hash::hash(&document)
+        );
+
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("1", 0))
+            .attachment(vec![0u8; MAX_ATTACHMENT_LEN + 1]);
+        assert!(matches!(
+            processor.issue(builder),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("1", 0))
+            .field(FieldType::Attachment, data::Revealed::String(s!("text")));
+        assert!(matches!(
+            processor.issue(builder),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_transfer_blinded_change() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let change_reveal = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 7,
+        };
+        let result = processor
+            .transfer(
+                TransferRequest::new(
+                    &mut asset,
+                    vec![outpoint(0)],
+                    vec![outcoins("4", 3)],
+                    vec![],
+                )
+                .blinded_change(change_reveal.clone()),
+            )
+            .unwrap();
+        // Change is not revealed in the transition
+        assert_eq!(revealed_outputs(&result.transition), vec![(3, 4)]);
+        assert!(result.blindings.contains_key(&change_reveal.conceal()));
+        assert_eq!(
+            asset.reveal_seal(&change_reveal.conceal()),
+            Some(outpoint(7))
+        );
+
+        asset.apply_transition(&result.transition);
+        assert_eq!(asset.balance(&[outpoint(7)]), 1);
+        let transition = processor
+            .transfer(
+                TransferRequest::new(
+                    &mut asset,
+                    vec![],
+                    vec![outcoins("1", 8)],
+                    vec![],
+                )
+                .confidential_input(change_reveal.conceal()),
+            )
+            .unwrap()
+            .transition;
+        assert_eq!(revealed_outputs(&transition), vec![(8, 1)]);
+    }
+
+    #[test]
+    fn test_export_genesis() {
+        let (_, genesis) =
+            issue(IssueStructure::SingleIssue, vec![outcoins("5", 0)], 0)
+                .unwrap();
+
+        let bech32m =
+            Processor::export_genesis(&genesis, GenesisFormat::Bech32m)
+                .unwrap();
+        assert!(String::from_utf8(bech32m.clone())
+            .unwrap()
+            .starts_with("genesis1"));
+        assert_eq!(
+            Processor::parse_genesis(&bech32m, GenesisFormat::Bech32m).unwrap(),
+            genesis
+        );
+        // Corrupted checksum is detected
+        let mut corrupted = bech32m.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] = if corrupted[last] == b'q' { b'p' } else { b'q' };
+        assert!(Processor::parse_genesis(&corrupted, GenesisFormat::Bech32m)
+            .is_err());
+
+        let binary =
+            Processor::export_genesis(&genesis, GenesisFormat::StrictEncode)
+                .unwrap();
+        assert_eq!(Genesis::strict_decode(&binary[..]).unwrap(), genesis);
+
+        #[cfg(feature = "base64")]
+        assert_eq!(
+            base64::decode(
+                Processor::export_genesis(&genesis, GenesisFormat::Base64)
+                    .unwrap()
+            )
+            .unwrap(),
+            binary
+        );
+    }
+
+    #[test]
+    fn test_validate_issue() {
+        let mut processor = processor();
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .precision(2)
+            .allocation(outcoins("1.5", 0))
+            .max_supply(1000)
+            .reissue_control(change_seal(1).unwrap());
+        let preview = processor.validate_issue(&builder).unwrap();
+        assert_eq!(*preview.issued_supply(), 150);
+        assert_eq!(*preview.total_supply(), 1000);
+        // Validation does not store the asset
+        assert!(processor
+            .asset_storage
+            .lock()
+            .unwrap()
+            .assets()
+            .unwrap()
+            .is_empty());
+        let (asset, _) = processor.issue(builder).unwrap();
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 150);
+
+        let invalid = vec![
+            // Total supply is less than issued
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                .allocation(outcoins("10", 0))
+                .max_supply(5)
+                .reissue_control(change_seal(1).unwrap()),
+            // Wrong ticker format
+            IssueBuilder::new(bp::Chain::Testnet3, "tick", "Ticker")
+                .allocation(outcoins("10", 0)),
+        ];
+        for builder in invalid {
+            let err = processor.validate_issue(&builder).unwrap_err();
+            assert!(matches!(err, ServiceErrorDomain::Schema(_)));
+            assert_eq!(processor.issue(builder).unwrap_err(), err);
+        }
+    }
+
+    #[test]
+    fn test_transfer_concealed() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let theirs = Outcoincealed {
+            coins: "1".parse().unwrap(),
+            seal_confidential: OutpointReveal {
+                blinding: 0x5a5a,
+                txid: txid(),
+                vout: 7,
+            }
+            .conceal(),
+        };
+        let result = processor
+            .transfer(
+                request(
+                    &mut asset,
+                    vec![outpoint(0)],
+                    vec![outcoins("3", 3)],
+                    vec![theirs],
+                    change_seal(4),
+                )
+                .conceal_amounts(),
+            )
+            .unwrap();
+        assert!(revealed_outputs(&result.transition).is_empty());
+        assert_eq!(result.reveals.len(), 3);
+        assert_eq!(
+            result
+                .reveals
+                .values()
+                .map(|reveal| reveal.value)
+                .sum::<u64>(),
+            5
+        );
+
+        let outputs = result
+            .transition
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .unwrap()
+            .to_discrete_state()
+            .into_iter()
+            .map(|state| match state {
+                OwnedState::ConfidentialAmount { assigned_state, .. }
+                | OwnedState::Confidential { assigned_state, .. } => {
+                    assigned_state
+                }
+                _ => panic!("Transfer output amount is not concealed"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(outputs.len(), 3);
+        for output in &outputs {
+            assert!(output.verify_bullet_proof().is_ok());
+        }
+        // Commitments of the outputs balance out the input commitment
+        let inputs = asset
+            .allocations(&outpoint(0))
+            .unwrap()
+            .iter()
+            .map(|alloc| alloc.value().conceal().commitment)
+            .collect();
+        assert!(value::Confidential::verify_commit_sum(
+            outputs
+                .into_iter()
+                .map(|output| output.commitment)
+                .collect(),
+            inputs
+        ));
+    }
+
+    #[test]
+    fn test_transfer_unspent() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let err = processor
+            .transfer(
+                TransferRequest::new(
+                    &mut asset,
+                    vec![outpoint(0), outpoint(1)],
+                    vec![outcoins("8", 3)],
+                    vec![],
+                )
+                .already_spent(outpoint(1)),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Transfer(TransferError::AlreadySpent(
+                outpoint(1)
+            ))
+        );
+        assert_eq!(asset.balance(&[outpoint(1)]), 3);
+
+        let result = processor
+            .transfer(
+                TransferRequest::new(
+                    &mut asset,
+                    vec![outpoint(0)],
+                    vec![outcoins("5", 3)],
+                    vec![],
+                )
+                .already_spent(outpoint(1)),
+            )
+            .unwrap();
+        assert_eq!(revealed_outputs(&result.transition), vec![(3, 5)]);
+    }
+
+    #[test]
+    fn test_issue_confidential_allocation() {
+        use lnpbp::bp::blind::OutpointReveal;
+        use lnpbp::client_side_validation::Conceal;
+
+        let reveal = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 7,
+        };
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("5", 0))
+            .confidential_allocation(Outcoincealed {
+                coins: "3".parse().unwrap(),
+                seal_confidential: reveal.conceal(),
+            });
+        let preview = processor().validate_issue(&builder).unwrap();
+        assert_eq!(*preview.issued_supply(), 8);
+
+        let (asset, genesis) = processor().issue(builder).unwrap();
+        assert_eq!(asset.issued_supply(), 8);
+        assert_eq!(asset.total_supply(), 8);
+        // Only the revealed allocation is known to the issuer asset state
+        assert_eq!(asset.total_balance(), 5);
+        let states = genesis
+            .owned_rights_by_type(*OwnedRightsType::Assets)
+            .unwrap()
+            .to_discrete_state();
+        assert_eq!(states.len(), 2);
+        assert_eq!(
+            states
+                .iter()
+                .filter(|state| matches!(
+                    state,
+                    OwnedState::ConfidentialSeal { .. }
+                ))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_match_blinded_seal() {
+        let blinded = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 1,
+        }
+        .conceal();
+        let candidates = vec![outpoint(0), outpoint(1), outpoint(2)];
+
+        let processor = processor();
+        assert_eq!(
+            processor.match_blinded_seal(blinded, candidates.clone(), 0x5a5a),
+            Some(outpoint(1))
+        );
+        // Wrong blinding factor does not reveal any of the candidates
+        assert_eq!(
+            processor.match_blinded_seal(blinded, candidates, 0xa5a5),
+            None
+        );
+        assert_eq!(
+            processor.match_blinded_seal(
+                blinded,
+                vec![outpoint(0), outpoint(2)],
+                0x5a5a
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_inflate_time_locked() {
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("1", 0))
+            .max_supply(10)
+            .reissue_control(change_seal(1).unwrap())
+            .inflation_not_before(700_000);
+        let (mut asset, genesis) = processor_v2().issue(builder).unwrap();
+        assert_eq!(*asset.inflation_not_before(), Some(700_000));
+        assert_eq!(
+            genesis.metadata().u32(*FieldType::InflationNotBefore),
+            vec![700_000]
+        );
+
+        let mut processor = processor();
+        let locked =
+            ServiceErrorDomain::Inflation(InflationError::TimeLocked {
+                not_before: 700_000,
+            });
+        assert_eq!(
+            processor
+                .inflate(
+                    &mut asset,
+                    outpoint(1),
+                    vec![outcoins("2", 2)],
+                    None,
+                    Some(699_999)
+                )
+                .unwrap_err(),
+            locked
+        );
+        // Without the current height time lock can't be checked
+        assert_eq!(
+            processor
+                .inflate(
+                    &mut asset,
+                    outpoint(1),
+                    vec![outcoins("2", 2)],
+                    None,
+                    None
+                )
+                .unwrap_err(),
+            locked
+        );
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 1);
+
+        processor
+            .inflate(
+                &mut asset,
+                outpoint(1),
+                vec![outcoins("2", 2)],
+                None,
+                Some(700_000),
+            )
+            .unwrap();
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 3);
+
+        // Time lock requires inflatable asset
+        assert!(IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("1", 0))
+            .inflation_not_before(700_000)
+            .spec()
+            .is_err());
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut asset = issue_burnable();
+        let mut processor = processor();
+        let transition = processor
+            .replace(
+                &mut asset,
+                outpoint(0),
+                vec![outcoins("2", 6), outcoins("3", 7)],
+                change_seal(8).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            transition.transition_type(),
+            *TransitionType::BurnAndReplace
+        );
+        assert_eq!(
+            transition.metadata().u64(*FieldType::BurnedSupply),
+            vec![5]
+        );
+        let mut outputs = revealed_outputs(&transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(6, 2), (7, 3)]);
+
+        // Supply is kept, while the allocations are moved to the new seals
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 8);
+        assert_eq!(asset.burned(), 0);
+        assert_eq!(asset.balance(&[outpoint(0)]), 0);
+        assert_eq!(asset.balance(&[outpoint(6), outpoint(7)]), 5);
+        assert_eq!(asset.total_balance(), 8);
+        assert!(asset
+            .right(&outpoint(5), OwnedRightsType::BurnReplace)
+            .is_none());
+        assert!(asset
+            .right(&outpoint(8), OwnedRightsType::BurnReplace)
+            .is_some());
+    }
+
+    #[test]
+    fn test_replace_amount_mismatch() {
+        let mut asset = issue_burnable();
+        let mut processor = processor();
+        let err = processor
+            .replace(
+                &mut asset,
+                outpoint(1),
+                vec![outcoins("2", 6), outcoins("2", 7)],
+                change_seal(8).unwrap(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServiceErrorDomain::Burn(BurnError::ReplaceMismatch {
+                burned: 3,
+                replaced: 4,
+            })
+        );
+        // Overflowing sum is not hidden by saturation
+        let err = processor
+            .replace(
+                &mut asset,
+                outpoint(1),
+                vec![outcoins("18446744073709551615", 6), outcoins("1", 7)],
+                change_seal(8).unwrap(),
+            )
+            .unwrap_err();
+        assert_eq!(err, ServiceErrorDomain::Burn(BurnError::AmountOverflow));
+        assert_eq!(asset.balance(&[outpoint(1)]), 3);
+        assert!(asset
+            .right(&outpoint(5), OwnedRightsType::BurnReplace)
+            .is_some());
+    }
+
+    #[test]
+    fn test_issue_builder_setters() {
+        let builder = IssueBuilder::with_network(bp::Chain::Mainnet)
+            .network(bp::Chain::Testnet3)
+            .ticker("TICK")
+            .name("Ticker")
+            .allocation(outcoins("5", 0));
+        let spec = builder.clone().spec().unwrap();
+        assert_eq!(spec.network, bp::Chain::Testnet3);
+        assert_eq!(spec.ticker, "TICK");
+        assert_eq!(spec.name, "Ticker");
+        assert_eq!(spec.issue_structure, IssueStructure::SingleIssue);
+        let (asset, _) = builder.build(&mut processor()).unwrap();
+        assert_eq!(asset.ticker(), "TICK");
+        assert_eq!(asset.supply().known_circulating().atomic_value(), 5);
+
+        let spec = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("5", 0))
+            .inflation(10, change_seal(1).unwrap())
+            .spec()
+            .unwrap();
+        assert!(matches!(
+            spec.issue_structure,
+            IssueStructure::MultipleIssues { max_supply: 10, .. }
+        ));
+
+        let mut processor = processor();
+        assert_eq!(
+            IssueBuilder::with_network(bp::Chain::Testnet3)
+                .name("Ticker")
+                .allocation(outcoins("5", 0))
+                .build(&mut processor)
+                .unwrap_err(),
+            ServiceErrorDomain::Schema(s!("Asset ticker is not set"))
+        );
+        assert_eq!(
+            IssueBuilder::with_network(bp::Chain::Testnet3)
+                .ticker("TICK")
+                .allocation(outcoins("5", 0))
+                .spec()
+                .unwrap_err(),
+            ServiceErrorDomain::Schema(s!("Asset name is not set"))
+        );
+    }
+
+    #[test]
+    fn test_max_outputs() {
+        let mut processor = processor();
+        processor.set_max_outputs(3);
+
+        // Two outputs with a change are at the limit
+        let mut asset = issue_spread();
+        assert!(transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("1", 3), outcoins("1", 4)],
+            vec![],
+            change_seal(5)
+        )
+        .is_ok());
+        let mut asset = issue_spread();
+        assert_eq!(
+            transfer(
+                &mut processor,
+                &mut asset,
+                vec![outpoint(0)],
+                vec![outcoins("1", 3), outcoins("1", 4), outcoins("1", 6)],
+                vec![],
+                change_seal(5)
+            )
+            .unwrap_err(),
+            ServiceErrorDomain::Transfer(TransferError::TooManyOutputs {
+                count: 4,
+                limit: 3
+            })
+        );
+        assert_eq!(asset.balance(&[outpoint(0)]), 5);
+
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("1", 0))
+            .allocation(outcoins("1", 1))
+            .allocation(outcoins("1", 2));
+        assert!(processor.validate_issue(&builder).is_ok());
+        assert!(matches!(
+            processor.validate_issue(&builder.allocation(outcoins("1", 3))),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_processor_with_schema() {
+        let mut custom = schema::schema();
+        custom.genesis.metadata.insert(
+            *FieldType::BurnedSupply,
+            lnpbp::rgb::schema::Occurences::NoneOrOnce,
+        );
+        assert_ne!(custom.schema_id(), schema::schema().schema_id());
+
+        let rgb_storage = Arc::new(Mutex::new(MemoryCache::new()));
+        let mut custom_processor = Processor::with_schema(
+            rgb_storage.clone(),
+            Arc::new(Mutex::new(MemoryCache::new())),
+            custom.clone(),
+        )
+        .unwrap();
+        assert_eq!(custom_processor.schema_id(), custom.schema_id());
+        assert!(rgb_storage
+            .lock()
+            .unwrap()
+            .has_schema(custom.schema_id())
+            .unwrap());
+
+        let (mut asset, genesis) = custom_processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                    .allocation(outcoins("5", 0)),
+            )
+            .unwrap();
+        assert_eq!(genesis.schema_id(), custom.schema_id());
+        assert_eq!(*asset.issuance_receipt().schema_id(), custom.schema_id());
+        assert!(transfer(
+            &mut custom_processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("5", 1)],
+            vec![],
+            None
+        )
+        .is_ok());
+        // Default processor does not accept the genesis of the custom schema
+        assert!(matches!(
+            processor().import_asset(genesis),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+
+        let mut incompatible = schema::schema();
+        incompatible.genesis.metadata.remove(&*FieldType::Ticker);
+        assert!(matches!(
+            Processor::with_schema(
+                Arc::new(Mutex::new(MemoryCache::new())),
+                Arc::new(Mutex::new(MemoryCache::new())),
+                incompatible,
+            ),
+            Err(BootstrapError::SchemaError)
+        ));
+    }
+
+    #[test]
+    fn test_indivisible_asset() {
+        let mut processor = processor();
+        let (mut asset, _) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "NFT", "Indivisible")
+                    .allocation(outcoins("3", 0)),
+            )
+            .unwrap();
+        assert_eq!(asset.precision(), 0);
+        assert_eq!(asset.issued_supply(), 3);
+
+        assert!(matches!(
+            processor.issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "NFT", "Indivisible")
+                    .allocation(outcoins("2.5", 0)),
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        // Insignificant trailing zeros are accepted
+        assert!(processor
+            .validate_issue(
+                &IssueBuilder::new(bp::Chain::Testnet3, "NFT", "Indivisible")
+                    .allocation(outcoins("2.0", 0)),
+            )
+            .is_ok());
+
+        assert!(matches!(
+            transfer(
+                &mut processor,
+                &mut asset,
+                vec![outpoint(0)],
+                vec![outcoins("2.5", 1)],
+                vec![],
+                change_seal(2)
+            ),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert_eq!(asset.balance(&[outpoint(0)]), 3);
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("2", 1)],
+            vec![],
+            change_seal(2),
+        )
+        .unwrap();
+        let mut outputs = revealed_outputs(&transition);
+        outputs.sort();
+        assert_eq!(outputs, vec![(1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn test_issue_deterministic() {
+        use lnpbp::client_side_validation::Conceal;
+
+        let reveal = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 2,
+        };
+        let mut seed = [0u8; 32];
+        seed[0] = 0x2a;
+        let builder = |seed: [u8; 32]| {
+            IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                .precision(2)
+                .allocation(outcoins("5", 0))
+                .allocation(outcoins("2.5", 1))
+                .confidential_allocation(Outcoincealed {
+                    coins: "1".parse().unwrap(),
+                    seal_confidential: reveal.conceal(),
+                })
+                .max_supply(1000)
+                .reissue_control(change_seal(3).unwrap())
+                .prune_seal(change_seal(4).unwrap())
+                .timestamp(1602340666)
+                .blinding_seed(seed)
+        };
+        let (asset1, genesis1) = processor_v2().issue(builder(seed)).unwrap();
+        let (asset2, genesis2) = processor_v2().issue(builder(seed)).unwrap();
+        assert_eq!(
+            strict_encoding::strict_encode(&genesis1).unwrap(),
+            strict_encoding::strict_encode(&genesis2).unwrap()
+        );
+        assert_eq!(genesis1.contract_id(), genesis2.contract_id());
+        assert_eq!(asset1, asset2);
+        assert_eq!(asset1.total_balance(), 750);
+
+        seed[0] = 0x2b;
+        let (_, genesis3) = processor_v2().issue(builder(seed)).unwrap();
+        assert_ne!(genesis1.contract_id(), genesis3.contract_id());
+    }
+
+    #[test]
+    fn test_issue_networks() {
+        let builder = |network| {
+            IssueBuilder::new(network, "TICK", "Ticker")
+                .allocation(outcoins("10", 0))
+        };
+        let mut processor = processor();
+        processor.set_issue_networks(vec![bp::Chain::Mainnet]);
+        assert!(matches!(
+            processor.issue(builder(bp::Chain::Testnet3)),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+        assert!(processor
+            .validate_issue(&builder(bp::Chain::Mainnet))
+            .is_ok());
+
+        // Without restrictions any network is accepted
+        assert!(processor()
+            .validate_issue(&builder(bp::Chain::Testnet3))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_local_commitment() {
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("3", 3)],
+            vec![],
+            change_seal(4),
+        )
+        .unwrap();
+
+        let opret = processor
+            .local_commitment(&transition, CommitMethod::OpReturn)
+            .unwrap();
+        let tapret = processor
+            .local_commitment(&transition, CommitMethod::Taproot)
+            .unwrap();
+        assert_eq!(
+            processor
+                .local_commitment(&transition, CommitMethod::OpReturn)
+                .unwrap(),
+            opret
+        );
+        assert_eq!(*opret.node_id(), transition.node_id());
+        assert_eq!(*tapret.node_id(), transition.node_id());
+        assert_ne!(opret.digest(), tapret.digest());
+
+        // Transitions of other schemata are not committed
+        let foreign = Transition::with(
+            u16::MAX as usize,
+            transition.metadata().clone(),
+            transition.parent_owned_rights().clone(),
+            transition.owned_rights().clone(),
+            bset![],
+            vec![],
+        );
+        assert!(matches!(
+            processor.local_commitment(&foreign, CommitMethod::OpReturn),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_dust_limit_issue() {
+        let builder = IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+            .allocation(outcoins("5", 0))
+            .allocation(outcoins("1", 1));
+        let mut processor = processor();
+        // Dust check is disabled by default
+        assert_eq!(processor.dust_limit(), None);
+        let (asset, _) = processor.issue(builder.clone()).unwrap();
+        assert!(processor.dust_allocations(&asset).is_empty());
+
+        processor.set_dust_limit(Some(2));
+        assert_eq!(processor.dust_limit(), Some(2));
+        match processor.validate_issue(&builder) {
+            Err(ServiceErrorDomain::Schema(msg)) => {
+                assert!(msg.contains("of 1 atomic units"))
+            }
+            _ => panic!("dust allocation is accepted"),
+        }
+        let dust = processor.dust_allocations(&asset);
+        assert_eq!(dust.len(), 1);
+        assert_eq!(*dust[0].outpoint(), outpoint(1));
+        assert_eq!(dust[0].value().value, 1);
+    }
+
+    #[test]
+    fn test_observers() {
+        let issued = Arc::new(Mutex::new(vec![]));
+        let transferred = Arc::new(Mutex::new(vec![]));
+        let mut processor = processor();
+        let observed = issued.clone();
+        processor.on_issue(Box::new(move |asset, genesis| {
+            observed
+                .lock()
+                .unwrap()
+                .push((*asset.id(), genesis.contract_id()))
+        }));
+        let observed = transferred.clone();
+        processor.on_transfer(Box::new(move |asset, transition| {
+            observed
+                .lock()
+                .unwrap()
+                .push((*asset.id(), transition.node_id()))
+        }));
+
+        let (mut asset, genesis) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Ticker")
+                    .allocation(outcoins("5", 0)),
+            )
+            .unwrap();
+        assert_eq!(
+            *issued.lock().unwrap(),
+            vec![(*asset.id(), genesis.contract_id())]
+        );
+        assert!(transferred.lock().unwrap().is_empty());
+
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("2", 1)],
+            vec![],
+            change_seal(2),
+        )
+        .unwrap();
+        assert_eq!(
+            *transferred.lock().unwrap(),
+            vec![(*asset.id(), transition.node_id())]
+        );
+
+        // Failed operations are not reported
+        assert!(transfer(
+            &mut processor,
+            &mut asset,
+            vec![outpoint(0)],
+            vec![outcoins("100", 1)],
+            vec![],
+            change_seal(2)
+        )
+        .is_err());
+        assert_eq!(issued.lock().unwrap().len(), 1);
+        assert_eq!(transferred.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_finalize() {
+        use lnpbp::bitcoin::Transaction;
+
+        let psbt = Psbt::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![],
+            output: vec![],
+        })
+        .unwrap();
+        let reveal = OutpointReveal {
+            blinding: 0x5a5a,
+            txid: txid(),
+            vout: 7,
+        };
+        let mut asset = issue_spread();
+        let mut processor = processor();
+        let inputs = vec![outpoint(0), outpoint(1)];
+        let transition = transfer(
+            &mut processor,
+            &mut asset,
+            inputs.clone(),
+            vec![outcoins("2", 3)],
+            vec![Outcoincealed {
+                coins: "4".parse().unwrap(),
+                seal_confidential: reveal.conceal(),
+            }],
+            change_seal(4),
+        )
+        .unwrap();
+
+        let request = processor
+            .finalize(&asset, transition.clone(), inputs.clone(), psbt.clone())
+            .unwrap();
+        assert_eq!(request.contract_id, *asset.id());
+        assert_eq!(request.inputs, inputs);
+        assert_eq!(request.transition, transition);
+        assert_eq!(request.outpoints, vec![reveal.conceal()]);
+        for alloc in inputs
+            .iter()
+            .flat_map(|seal| asset.allocations(seal).unwrap())
+        {
+            assert!(request
+                .transition
+                .parent_owned_rights()
+                .contains_key(alloc.node_id()));
+        }
+
+        // Inputs must hold the allocations closed by the transition
+        assert!(matches!(
+            processor.finalize(&asset, transition, vec![outpoint(2)], psbt),
+            Err(ServiceErrorDomain::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_not_stored() {
+        let mut processor = processor();
+        let (asset, genesis) = processor
+            .issue(
+                IssueBuilder::new(bp::Chain::Testnet3, "TICK", "Test asset")
+                    .allocation(outcoins("10", 0))
+                    .store(false),
+            )
+            .unwrap();
+        assert!(!processor
+            .asset_storage
+            .lock()
+            .unwrap()
+            .has_asset(*asset.id())
+            .unwrap());
+        // Once the genesis is accepted the asset is stored exactly once
+        assert_eq!(processor.import_asset(genesis.clone()).unwrap(), asset);
+        assert!(processor.import_asset(genesis).is_err());
     }
 }
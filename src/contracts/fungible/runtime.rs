@@ -14,6 +14,7 @@
 use ::core::borrow::Borrow;
 use ::core::convert::TryFrom;
 use ::std::path::PathBuf;
+use ::std::sync::{Arc, Mutex, MutexGuard};
 
 use lnpbp::bitcoin::OutPoint;
 use lnpbp::client_side_validation::Conceal;
@@ -25,9 +26,12 @@ use lnpbp::lnp::{
 };
 use lnpbp::rgb::{Assignments, Consignment, ContractId, Genesis, Node};
 
-use super::cache::{Cache, FileCache, FileCacheConfig};
+use super::cache::{Cache, FileCache, FileCacheConfig, MemoryCache};
 use super::schema::OwnedRightsType;
-use super::{schema, Asset, Config, IssueStructure, Processor};
+use super::{
+    schema, Asset, Config, IssueBuilder, IssueStructure, Processor,
+    TransferRequest,
+};
 use crate::api::stash::MergeRequest;
 use crate::api::{
     self,
@@ -62,8 +66,9 @@ pub struct Runtime {
     stash_sub: session::Raw<PlainTranscoder, transport::zmqsocket::Connection>,
 
     /// RGB fungible assets data cache: relational database sharing the client-
-    /// friendly asset information with clients
-    cacher: FileCache,
+    /// friendly asset information with clients. The cache is shared with the
+    /// processor, which persists newly created assets into it.
+    cacher: Arc<Mutex<FileCache>>,
 
     /// Processor instance: handles business logic outside of stash scope
     processor: Processor,
@@ -80,21 +85,29 @@ impl Runtime {
     /// use and reduce number of errors. Cacher may be switched with compile
     /// configuration options and, thus, we need to make sure that the structure
     /// we use corresponds to certain trait and not specific type.
-    fn cache(&self) -> &impl Cache {
-        &self.cacher
+    fn cache(&self) -> Result<MutexGuard<'_, impl Cache>, ServiceErrorDomain> {
+        Ok(self.cacher.lock()?)
     }
 
     pub fn init(config: Config) -> Result<Self, BootstrapError> {
-        let processor = Processor::new()?;
-
-        let cacher = FileCache::new(FileCacheConfig {
-            data_dir: PathBuf::from(&config.cache),
-            data_format: config.format,
-        })
-        .map_err(|err| {
-            error!("{}", err);
-            err
-        })?;
+        let cacher = Arc::new(Mutex::new(
+            FileCache::new(FileCacheConfig {
+                data_dir: PathBuf::from(&config.cache),
+                data_format: config.format,
+            })
+            .map_err(|err| {
+                error!("{}", err);
+                err
+            })?,
+        ));
+
+        // Schema is registered in the stash daemon via RPC, so the processor
+        // does not need persistent RGB storage
+        let mut processor = Processor::new(
+            Arc::new(Mutex::new(MemoryCache::new())),
+            cacher.clone(),
+        )?;
+        processor.set_chain(config.network.clone());
 
         let session_rpc = session::Raw::with_zmq_unencrypted(
             ZmqType::Rep,
@@ -219,27 +232,51 @@ impl Runtime {
 
         let issue_structure = match issue.inflatable {
             None => IssueStructure::SingleIssue,
-            Some(ref seal_spec) => IssueStructure::MultipleIssues {
-                max_supply: issue.supply.ok_or(ServiceErrorDomain::Api(
+            Some(ref seal_spec) => {
+                let supply = issue.supply.ok_or(ServiceErrorDomain::Api(
                     ApiErrorType::MissedArgument {
                         request: "Issue".to_string(),
                         argument: "supply".to_string(),
                     },
-                ))?,
-                reissue_control: seal_spec.clone(),
-            },
+                ))?;
+                // Supply which can't be represented with the asset precision
+                // must not be silently truncated or saturated
+                let malformed = || {
+                    ServiceErrorDomain::Api(ApiErrorType::MalformedArgument {
+                        request: "Issue".to_string(),
+                        argument: "supply".to_string(),
+                    })
+                };
+                if supply.exceeds_precision(issue.precision) {
+                    Err(malformed())?
+                }
+                IssueStructure::MultipleIssues {
+                    max_supply: supply
+                        .checked_atomic_value(issue.precision)
+                        .ok_or_else(malformed)?,
+                    reissue_control: seal_spec.clone(),
+                    inflation_not_before: None,
+                }
+            }
         };
 
-        let (asset, genesis) = self.processor.issue(
+        // The asset is persisted only once the stash daemon accepts the
+        // genesis, so it is not stored by the processor itself
+        let mut builder = IssueBuilder::new(
             self.config.network.clone(),
-            issue.ticker.clone(),
-            issue.title.clone(),
-            issue.description.clone(),
-            issue_structure,
-            issue.allocate.clone(),
-            issue.precision,
-            vec![],
-        )?;
+            &issue.ticker,
+            &issue.title,
+        )
+        .precision(issue.precision)
+        .issue_structure(issue_structure)
+        .store(false);
+        if let Some(ref description) = issue.description {
+            builder = builder.description(description);
+        }
+        for allocation in &issue.allocate {
+            builder = builder.allocation(allocation.clone());
+        }
+        let (asset, genesis) = self.processor.issue(builder)?;
 
         self.import_asset(asset, genesis).await?;
 
@@ -258,16 +295,20 @@ impl Runtime {
         // of       asset for the transfer operation
 
         trace!("Looking for asset information");
-        let mut asset = self.cacher.asset(transfer.contract_id)?.clone();
+        let mut asset =
+            self.cacher.lock()?.asset(transfer.contract_id)?.clone();
         debug!("Transferring asset {}", asset);
 
         trace!("Preparing state transition");
-        let transition = self.processor.transfer(
-            &mut asset,
-            transfer.inputs.clone(),
-            transfer.ours.clone(),
-            transfer.theirs.clone(),
-        )?;
+        let transition = self
+            .processor
+            .transfer(TransferRequest::new(
+                &mut asset,
+                transfer.inputs.clone(),
+                transfer.ours.clone(),
+                transfer.theirs.clone(),
+            ))?
+            .transition;
         debug!("State transition: {}", transition);
 
         trace!("Requesting consignment from stash daemon");
@@ -320,7 +361,7 @@ impl Runtime {
         data_format: DataFormat,
     ) -> Result<Reply, ServiceErrorDomain> {
         debug!("Got SYNC");
-        let data = self.cacher.export(Some(data_format))?;
+        let data = self.cacher.lock()?.export(Some(data_format))?;
         Ok(Reply::Sync(reply::SyncFormat(self.config.format, data)))
     }
 
@@ -329,7 +370,7 @@ impl Runtime {
         outpoint: OutPoint,
     ) -> Result<Reply, ServiceErrorDomain> {
         debug!("Got ASSETS");
-        let data = self.cacher.outpoint_assets(outpoint)?;
+        let data = self.cacher.lock()?.outpoint_assets(outpoint)?;
         Ok(Reply::Assets(data))
     }
 
@@ -338,7 +379,7 @@ impl Runtime {
         contract_id: ContractId,
     ) -> Result<Reply, ServiceErrorDomain> {
         debug!("Got ALLOCATIONS");
-        let data = self.cacher.asset_allocations(contract_id)?;
+        let data = self.cacher.lock()?.asset_allocations(contract_id)?;
         Ok(Reply::Allocations(data))
     }
 
@@ -380,7 +421,7 @@ impl Runtime {
             .stash_req_rep(api::stash::Request::AddGenesis(genesis))
             .await?
         {
-            Reply::Success => Ok(self.cacher.add_asset(asset)?),
+            Reply::Success => Ok(self.cacher.lock()?.add_asset(asset)?),
             _ => Err(ServiceErrorDomain::Api(ApiErrorType::UnexpectedReply)),
         }
     }
@@ -438,8 +479,8 @@ impl Runtime {
             .await?;
         if let Reply::Success = reply {
             let asset_id = accept.consignment.genesis.contract_id();
-            let mut asset = if self.cacher.has_asset(asset_id)? {
-                self.cacher.asset(asset_id)?.clone()
+            let mut asset = if self.cacher.lock()?.has_asset(asset_id)? {
+                self.cacher.lock()?.asset(asset_id)?.clone()
             } else {
                 Asset::try_from(accept.consignment.genesis)?
             };
@@ -460,8 +501,8 @@ impl Runtime {
                                 if let Some(assigned_state) =
                                     assignment.assigned_state()
                                 {
-                                    asset.add_allocation(
-                                        seal.clone().into(),
+                                    asset.add_confidential_allocation(
+                                        seal.clone(),
                                         transition.node_id(),
                                         index as u16,
                                         assigned_state.clone(),
@@ -478,7 +519,7 @@ impl Runtime {
                 }
             }
 
-            self.cacher.add_asset(asset)?;
+            self.cacher.lock()?.add_asset(asset)?;
             Ok(reply)
         } else if let Reply::Failure(_) = &reply {
             Ok(reply)
@@ -494,6 +535,7 @@ impl Runtime {
         let mut removal_list = Vec::<_>::new();
         let assets = self
             .cacher
+            .lock()?
             .assets()?
             .into_iter()
             .map(Clone::clone)
@@ -513,7 +555,7 @@ impl Runtime {
                 );
                 removal_list.push((*allocation.node_id(), *allocation.index()));
             }
-            self.cacher.add_asset(asset)?;
+            self.cacher.lock()?.add_asset(asset)?;
         }
         if removal_list.is_empty() {
             return Ok(Reply::Nothing);
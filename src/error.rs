@@ -15,6 +15,7 @@
 
 use std::collections::HashMap;
 use std::io;
+use std::sync::PoisonError;
 use tokio::task::JoinError;
 
 use lnpbp::lnp;
@@ -50,9 +51,18 @@ pub enum BootstrapError {
     #[from(crate::contracts::fungible::SqlCacheError)]
     CacheError,
 
+    #[from(crate::contracts::fungible::schema::Error)]
+    SchemaError,
+
     Other,
 }
 
+impl<T> From<PoisonError<T>> for BootstrapError {
+    fn from(_: PoisonError<T>) -> Self {
+        BootstrapError::StorageError
+    }
+}
+
 impl From<&str> for BootstrapError {
     fn from(err: &str) -> Self {
         BootstrapError::ArgParseError(err.to_string())
@@ -133,6 +143,7 @@ pub enum ServiceErrorDomain {
     #[from(crate::contracts::fungible::FileCacheError)]
     #[from(crate::contracts::fungible::SqlCacheError)]
     Cache,
+    #[from(JoinError)]
     Multithreading,
     P2pwire,
     #[from]
@@ -149,9 +160,25 @@ pub enum ServiceErrorDomain {
     Schema(String),
     Anchor(String),
     #[from]
+    Transfer(crate::contracts::fungible::TransferError),
+    #[from]
+    Inflation(crate::contracts::fungible::InflationError),
+    #[from]
+    Burn(crate::contracts::fungible::BurnError),
+    #[from]
+    Renomination(crate::contracts::fungible::RenominationError),
+    #[from]
+    Validation(crate::contracts::fungible::ValidationError),
+    #[from]
     Internal(String),
 }
 
+impl<T> From<PoisonError<T>> for ServiceErrorDomain {
+    fn from(_: PoisonError<T>) -> Self {
+        ServiceErrorDomain::Multithreading
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Display)]
 #[display(Debug)]
 pub enum ServiceErrorSource {
@@ -31,7 +31,7 @@ use crate::api::{
 };
 use crate::error::ServiceErrorDomain;
 use crate::fungible::{
-    Invoice, IssueStructure, Outcoincealed, Outcoins, Outpoint,
+    DecimalAmount, Invoice, IssueStructure, Outcoincealed, Outcoins, Outpoint,
 };
 use crate::util::file::ReadWrite;
 use crate::util::SealSpec;
@@ -66,7 +66,11 @@ impl Runtime {
             IssueStructure::MultipleIssues {
                 max_supply,
                 reissue_control,
-            } => (Some(max_supply), Some(reissue_control)),
+                ..
+            } => (
+                Some(DecimalAmount::from_atomic_value(precision, max_supply)),
+                Some(reissue_control),
+            ),
         };
         let command = Request::Issue(Issue {
             ticker,
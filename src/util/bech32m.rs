@@ -0,0 +1,195 @@
+// RGB standard library
+// Written in 2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Bech32m encoding (BIP-350) of arbitrary binary data. The bech32 library
+//! used by the dependencies supports the original Bech32 checksum only. The
+//! length limit of BIP-173 addresses is not applied, since encoded RGB data
+//! are much longer.
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+const CHECKSUM_LEN: usize = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum Error {
+    /// Bech32m string contains both lowercase and uppercase characters
+    MixedCase,
+
+    /// Bech32m string has no separator, empty human-readable part or too
+    /// short checksum
+    InvalidFormat,
+
+    /// Bech32m string contains invalid character `{_0}`
+    InvalidChar(char),
+
+    /// Bech32m checksum does not match the data
+    InvalidChecksum,
+
+    /// Bech32m data part has non-zero padding
+    InvalidPadding,
+}
+
+/// Encodes `data` as Bech32m string with lowercase human-readable part `hrp`
+pub fn encode(hrp: &str, data: &[u8]) -> String {
+    let values = convert_bits(data, 8, 5, true)
+        .expect("8-bit values always convert into 5-bit groups");
+    let mut checked = hrp_expand(hrp);
+    checked.extend(&values);
+    checked.extend(&[0u8; CHECKSUM_LEN]);
+    let checksum = polymod(&checked) ^ BECH32M_CONST;
+
+    let mut result = format!("{}1", hrp);
+    result.extend(values.iter().map(|value| CHARSET[*value as usize] as char));
+    result.extend((0..CHECKSUM_LEN).map(|i| {
+        CHARSET[((checksum >> (5 * (CHECKSUM_LEN - 1 - i))) & 0x1f) as usize]
+            as char
+    }));
+    result
+}
+
+/// Decodes Bech32m string into the lowercase human-readable part and data
+pub fn decode(s: &str) -> Result<(String, Vec<u8>), Error> {
+    if s.chars().any(|c| c.is_ascii_lowercase())
+        && s.chars().any(|c| c.is_ascii_uppercase())
+    {
+        Err(Error::MixedCase)?
+    }
+    let s = s.to_ascii_lowercase();
+    let pos = s.rfind('1').ok_or(Error::InvalidFormat)?;
+    if pos == 0 || pos + 1 + CHECKSUM_LEN > s.len() {
+        Err(Error::InvalidFormat)?
+    }
+    let (hrp, data) = (&s[..pos], &s[pos + 1..]);
+    if let Some(c) = hrp.chars().find(|c| !('!'..='~').contains(c)) {
+        Err(Error::InvalidChar(c))?
+    }
+    let values = data
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|symbol| *symbol as char == c)
+                .map(|value| value as u8)
+                .ok_or(Error::InvalidChar(c))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let mut checked = hrp_expand(hrp);
+    checked.extend(&values);
+    if polymod(&checked) != BECH32M_CONST {
+        Err(Error::InvalidChecksum)?
+    }
+    let data =
+        convert_bits(&values[..values.len() - CHECKSUM_LEN], 5, 8, false)
+            .ok_or(Error::InvalidPadding)?;
+    Ok((hrp.to_string(), data))
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    values.iter().fold(1u32, |chk, value| {
+        let top = chk >> 25;
+        GENERATOR.iter().enumerate().fold(
+            ((chk & 0x01ff_ffff) << 5) ^ *value as u32,
+            |chk, (i, generator)| {
+                if (top >> i) & 1 == 1 {
+                    chk ^ generator
+                } else {
+                    chk
+                }
+            },
+        )
+    })
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut values = hrp.bytes().map(|b| b >> 5).collect::<Vec<_>>();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 0x1f));
+    values
+}
+
+/// Regroups `data` of `from`-bit values into `to`-bit values, returning
+/// `None` if a value does not fit into `from` bits or, without `pad`, if the
+/// remaining bits are not a zero padding
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let max_value = (1u32 << to) - 1;
+    let max_acc = (1u32 << (from + to - 1)) - 1;
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let mut result =
+        Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        let value = *value as u32;
+        if value >> from != 0 {
+            return None;
+        }
+        acc = ((acc << from) | value) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return None;
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bip350_vectors() {
+        for s in &[
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ] {
+            assert!(decode(s).is_ok(), "{} is rejected", s);
+        }
+        assert_eq!(decode("a1lqfn3a").unwrap(), (s!("a"), vec![]));
+        assert_eq!(encode("a", &[]), "a1lqfn3a");
+        // Valid Bech32 (not Bech32m) checksum
+        assert_eq!(decode("a12uel5l"), Err(Error::InvalidChecksum));
+        assert_eq!(decode("A1lqfn3a"), Err(Error::MixedCase));
+        assert_eq!(decode("1lqfn3a"), Err(Error::InvalidFormat));
+        assert_eq!(decode("a1lqfn3"), Err(Error::InvalidFormat));
+        assert_eq!(decode("a1lqfn3b"), Err(Error::InvalidChar('b')));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = (0u8..=255).collect::<Vec<_>>();
+        let encoded = encode("genesis", &data);
+        assert!(encoded.starts_with("genesis1"));
+        assert_eq!(decode(&encoded).unwrap(), (s!("genesis"), data));
+    }
+}
@@ -37,10 +37,43 @@ pub struct SealSpec {
 }
 
 impl SealSpec {
+    /// Constructs seal on the output of the witness (anchoring) transaction,
+    /// which txid is not known yet
+    #[inline]
+    pub fn with_witness_vout(vout: u32) -> Self {
+        Self { vout, txid: None }
+    }
+
+    /// Converts seal definition on the witness transaction output into the
+    /// one on the transaction outpoint once the witness `txid` is known,
+    /// keeping the blinding factor. Other seal definitions are returned
+    /// unchanged.
+    pub fn resolve_witness(
+        seal_definition: SealDefinition,
+        txid: Txid,
+    ) -> SealDefinition {
+        match seal_definition {
+            SealDefinition::WitnessVout { vout, blinding } => {
+                SealDefinition::TxOutpoint(bp::blind::OutpointReveal {
+                    blinding,
+                    txid,
+                    vout,
+                })
+            }
+            seal_definition => seal_definition,
+        }
+    }
+
     pub fn seal_definition(&self) -> SealDefinition {
         use lnpbp::bitcoin::secp256k1::rand::{self, RngCore};
         let mut rng = rand::thread_rng();
         let entropy = rng.next_u64(); // Not an amount blinding factor but outpoint blinding
+        self.seal_definition_with(entropy)
+    }
+
+    /// Constructs seal definition with a given outpoint blinding `entropy`
+    /// instead of a random one, which allows reproducible seal definitions
+    pub fn seal_definition_with(&self, entropy: u64) -> SealDefinition {
         match self.txid {
             Some(txid) => {
                 SealDefinition::TxOutpoint(bp::blind::OutpointReveal {
@@ -84,8 +117,8 @@ impl FromStr for SealSpec {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let re = Regex::new(
             r"(?x)
-                ((?P<txid>[a-f\d]{64}) # Txid
-                :)
+                ^((?P<txid>[a-f\d]{64}) # Txid
+                :)? # Witness transaction if omitted
                 (?P<vout>\d+)$ # Vout
             ",
         )
@@ -107,3 +140,47 @@ impl FromStr for SealSpec {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn txid() -> Txid {
+        Txid::from_hex(
+            "db2f3035e05795d72e2744dc0e88b2f72acbed97ee9a54c2c7f52d426ae05627",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_witness_vout_seal() {
+        let seal_spec = SealSpec::with_witness_vout(3);
+        assert_eq!(seal_spec, "3".parse().unwrap());
+        let seal_definition = seal_spec.seal_definition();
+        let blinding = match seal_definition {
+            SealDefinition::WitnessVout { vout, blinding } => {
+                assert_eq!(vout, 3);
+                blinding
+            }
+            _ => panic!("Witness vout seal definition expected"),
+        };
+
+        assert_eq!(
+            SealSpec::resolve_witness(seal_definition, txid()),
+            SealDefinition::TxOutpoint(bp::blind::OutpointReveal {
+                blinding,
+                txid: txid(),
+                vout: 3,
+            })
+        );
+        let seal_definition = SealSpec {
+            vout: 3,
+            txid: Some(txid()),
+        }
+        .seal_definition();
+        assert_eq!(
+            SealSpec::resolve_witness(seal_definition, txid()),
+            seal_definition
+        );
+    }
+}